@@ -62,6 +62,8 @@
               };
               allowBuiltinFetchGit = true;
             };
+            buildNoDefaultFeatures = true;
+            buildFeatures = [ "server" ];
 
             nativeBuildInputs = [ pkgs.pkg-config ];
             buildInputs = [ pkgs.openssl ];
@@ -24,6 +24,7 @@ impl TryFrom<Html> for Group<Allergen> {
 
         Ok(Group {
             name: "allergens".into(),
+            course: None,
             items: {
                 let mut result = Vec::new();
                 for element_ref in html.select(&group_selector) {
@@ -38,7 +39,7 @@ impl TryFrom<Html> for Group<Allergen> {
                         })
                     }
                 }
-                result.sort_by_key(|a| a.number.clone());
+                result.sort_by_key(|a| a.number);
                 result
             },
         })
@@ -57,6 +58,21 @@ impl Display for Allergen {
     }
 }
 
+impl Allergen {
+    /// The code used in meal allergen lists, e.g. "22a".
+    pub fn code(&self) -> String {
+        format!(
+            "{}{}",
+            self.number,
+            self.index.map(String::from).unwrap_or_default()
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 pub fn get() -> Result<Group<Allergen>, Error> {
     serde_json::from_str::<Group<Allergen>>(ALLERGENS_DATA)
         .map_err(|e| Error::Parse(format!("Allergens\n< {}", e)))
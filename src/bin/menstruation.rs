@@ -1,4 +1,10 @@
-use menstruation::*;
+use chrono::NaiveTime;
+use menstruation::{
+    i18n::{Language, Text},
+    *,
+};
+use std::io::{self, Write};
+use std::process;
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -6,37 +12,358 @@ use structopt::StructOpt;
     rename_all = "kebab-case",
     setting = structopt::clap::AppSettings::ColoredHelp
 )]
-enum Options {
-    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
-    /// Displays the menu
+struct Options {
+    #[structopt(
+        long,
+        global = true,
+        parse(try_from_str),
+        help = i18n::text(Text::HelpLang)
+    )]
+    lang: Option<Language>,
+    #[structopt(
+        long,
+        global = true,
+        parse(try_from_str = dates::parse_time),
+        help = i18n::text(Text::HelpRollover)
+    )]
+    rollover: Option<NaiveTime>,
+    #[structopt(
+        short,
+        long,
+        global = true,
+        conflicts_with = "count",
+        help = i18n::text(Text::HelpQuiet)
+    )]
+    quiet: bool,
+    #[structopt(long, global = true, help = i18n::text(Text::HelpCount))]
+    count: bool,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+enum Command {
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpMenuCommand)
+    )]
     Menu(menu::MenuOptions),
-    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
-    /// Lists all available dining facilities
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpCodesCommand)
+    )]
     Codes {
-        #[structopt(name = "PATTERN")]
-        /// Searches for a specific pattern
+        #[structopt(name = "PATTERN", help = i18n::text(Text::HelpCodesPattern))]
         pattern: Option<String>,
+        #[structopt(short, long, help = i18n::text(Text::HelpCodesUniversity))]
+        university: Option<String>,
+        #[structopt(
+            long,
+            value_name = "LAT,LON",
+            parse(try_from_str),
+            help = i18n::text(Text::HelpNear)
+        )]
+        near: Option<geo::Coordinates>,
+        #[structopt(long, default_value = "5", help = i18n::text(Text::HelpNearLimit))]
+        limit: usize,
+        #[structopt(long, hidden = true)]
+        /// Prints the registry with opening hours scraped from stw.berlin, to update data/codes.json
+        scrape_hours: bool,
     },
-    #[structopt(setting = structopt::clap::AppSettings::ColoredHelp)]
-    /// List all allergen codes
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpAllergensCommand)
+    )]
     Allergens,
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpFavoritesCommand)
+    )]
+    Favorites(favorites::FavoritesCommand),
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpSearchCommand)
+    )]
+    Search(search::SearchOptions),
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpTrayCommand)
+    )]
+    Tray(tray::TrayOptions),
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpPlanCommand)
+    )]
+    Plan(plan::PlanOptions),
+    #[cfg(feature = "tui")]
+    #[structopt(
+        setting = structopt::clap::AppSettings::ColoredHelp,
+        about = i18n::text(Text::HelpTuiCommand)
+    )]
+    Tui(menu::MenuOptions),
+}
+
+/// The language of the output without `--lang`, as clap's own messages are in English. The menus
+/// are still fetched in German then, like on the server without `lang=`.
+const DEFAULT_LANGUAGE: Language = Language::En;
+
+/// Picks up `--lang` before the other arguments are parsed, so that their error messages are
+/// already in the chosen language.
+fn language_from_args() -> Language {
+    let arguments = std::env::args().collect::<Vec<_>>();
+    arguments
+        .iter()
+        .enumerate()
+        .find_map(|(index, argument)| match argument.strip_prefix("--lang") {
+            Some("") => arguments.get(index + 1)?.parse().ok(),
+            Some(value) => value.strip_prefix('=')?.parse().ok(),
+            None => None,
+        })
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// The exit code when a command found nothing, e.g. no meal matching the filters.
+const NO_MATCHES: i32 = 1;
+/// The exit code for arguments that could not be parsed; errors of the library have their own.
+const INVALID_ARGUMENTS: i32 = 2;
+
+/// The texts that clap generates itself, which are translated in its help and error messages.
+const CLAP_TEXTS: &[(&str, Text)] = &[
+    ("USAGE:", Text::HelpHeadingUsage),
+    ("FLAGS:", Text::HelpHeadingFlags),
+    ("OPTIONS:", Text::HelpHeadingOptions),
+    ("ARGS:", Text::HelpHeadingArgs),
+    ("SUBCOMMANDS:", Text::HelpHeadingSubcommands),
+    (
+        "Prints this message or the help of the given subcommand(s)",
+        Text::HelpHelpSubcommand,
+    ),
+    ("Prints help information", Text::HelpHelp),
+    ("Prints version information", Text::HelpVersion),
+    ("For more information try --help", Text::HelpMoreInformation),
+    ("error:", Text::HelpErrorPrefix),
+    ("[default: ", Text::HelpDefault),
+    ("Invalid value for", Text::HelpErrorInvalidValue),
+    ("Found argument", Text::HelpErrorFoundArgument),
+    (
+        "which wasn't expected, or isn't valid in this context",
+        Text::HelpErrorUnexpected,
+    ),
+    (
+        "The following required arguments were not provided:",
+        Text::HelpErrorMissingArguments,
+    ),
+    (
+        "requires a value but none was supplied",
+        Text::HelpErrorMissingValue,
+    ),
+    ("cannot be used with", Text::HelpErrorConflict),
+    ("The argument", Text::HelpErrorArgument),
+    ("isn't a valid value for", Text::HelpErrorPossibleValue),
+    ("[possible values:", Text::HelpErrorPossibleValues),
+    ("The subcommand", Text::HelpErrorSubcommand),
+    ("wasn't recognized", Text::HelpErrorUnrecognized),
+    (
+        "requires a subcommand, but one was not provided",
+        Text::HelpErrorMissingSubcommand,
+    ),
+    (
+        "If you believe you received this message in error, try re-running with",
+        Text::HelpErrorRetry,
+    ),
+    ("Did you mean", Text::HelpErrorDidYouMean),
+];
+
+fn localize_clap(message: &str) -> String {
+    CLAP_TEXTS
+        .iter()
+        .fold(message.to_string(), |message, (english, text)| {
+            message.replace(english, i18n::text(*text))
+        })
 }
 
 fn main() {
-    let options = Options::from_args();
-
-    match options {
-        Options::Menu(menu_options) => match menu::get(menu_options) {
-            Ok(menu_response) => println!("{}", menu_response),
-            Err(e) => eprintln!("{}", e),
-        },
-        Options::Codes { pattern } => match codes::get(pattern) {
-            Ok(codes_response) => println!("{}", codes_response),
-            Err(e) => eprintln!("{}", e),
-        },
-        Options::Allergens => match allergens::get() {
-            Ok(allergen_group) => println!("{}", allergen_group),
-            Err(e) => eprintln!("{}", e),
-        },
+    i18n::set_language(language_from_args());
+    let options = match Options::from_iter_safe(std::env::args()) {
+        Ok(options) => options,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", localize_clap(&e.message));
+            process::exit(INVALID_ARGUMENTS)
+        }
+        // --help and --version
+        Err(e) => {
+            // like clap, ignore a closed pipe, e.g. with `| head`
+            let _ = writeln!(io::stdout(), "{}", localize_clap(&e.message));
+            process::exit(0)
+        }
+    };
+    i18n::set_language(options.lang.unwrap_or(DEFAULT_LANGUAGE));
+    dates::set_rollover(options.rollover);
+
+    let print = !options.quiet && !options.count;
+    // the menus are German unless another language is chosen explicitly
+    match run(options.command, options.lang.unwrap_or(Language::De), print) {
+        Ok(count) => {
+            if let (true, Some(count)) = (options.count, count) {
+                println!("{}", count);
+            }
+            if count == Some(0) {
+                process::exit(NO_MATCHES)
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(e.exit_code())
+        }
+    }
+}
+
+/// Prints warnings about incomplete results to the standard error.
+fn print_warnings(warnings: &[String]) {
+    if !warnings.is_empty() {
+        eprintln!(
+            "{}",
+            ansi_term::Colour::Yellow
+                .bold()
+                .paint(i18n::text(Text::Warnings))
+        );
+        for warning in warnings {
+            eprintln!("{}", warning);
+        }
+    }
+}
+
+/// Runs a command, printing its results if `print` is set, and returns their number for the
+/// commands that list something.
+fn run(command: Command, language: Language, print: bool) -> Result<Option<usize>, Error> {
+    match command {
+        Command::Menu(menu_options) => {
+            let menu = menu::get(menu_options, language)?;
+            if print {
+                println!("{}", menu);
+                print_warnings(&menu.groups.warnings());
+            }
+            Ok(Some(menu.groups.len()))
+        }
+        Command::Codes {
+            scrape_hours: true, ..
+        } => {
+            let (mensas, warnings) = codes::scrape_opening_hours()?;
+            if print {
+                let json = serde_json::to_string_pretty(&mensas)
+                    .map_err(|e| Error::Parse(format!("Codes\n< {}", e)))?;
+                println!("{}", json);
+                print_warnings(&warnings);
+            }
+            Ok(Some(mensas.len()))
+        }
+        Command::Codes {
+            near: Some(position),
+            limit,
+            ..
+        } => {
+            let nearby = codes::nearest(&position, limit)?;
+            if print {
+                for mensa in &nearby {
+                    print!("{}", mensa);
+                }
+            }
+            Ok(Some(nearby.len()))
+        }
+        Command::Codes {
+            pattern,
+            university,
+            ..
+        } => {
+            let mensas = codes::get(pattern, university)?;
+            if print {
+                for mensa in &mensas {
+                    print!("{}", mensa);
+                }
+            }
+            Ok(Some(mensas.len()))
+        }
+        Command::Allergens => {
+            let allergen_group = allergens::get()?;
+            if print {
+                println!("{}", allergen_group);
+            }
+            Ok(None)
+        }
+        Command::Search(search_options) => {
+            let (hits, warnings) = search::get(search_options)?;
+            if print {
+                for hit in &hits {
+                    print!("{}", hit);
+                }
+                print_warnings(&warnings);
+            }
+            Ok(Some(hits.len()))
+        }
+        Command::Tray(tray_options) => {
+            let trays = tray::get(tray_options, language)?;
+            if print && trays.is_empty() {
+                println!("{}", i18n::text(Text::NoTray));
+            } else if print {
+                for tray in &trays {
+                    print!("{}", tray);
+                }
+            }
+            Ok(Some(trays.len()))
+        }
+        Command::Plan(plan_options) => {
+            let plan = plan::get(plan_options, language)?;
+            if print {
+                print!("{}", plan);
+            }
+            Ok(Some(
+                plan.days.iter().filter(|day| day.meal.is_some()).count(),
+            ))
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui(menu_options) => tui::run(menu_options, language).map(|_| None),
+        Command::Favorites(command) => run_favorites(command, print),
+    }
+}
+
+fn run_favorites(
+    command: favorites::FavoritesCommand,
+    print: bool,
+) -> Result<Option<usize>, Error> {
+    let mut registered = favorites::load()?;
+    match command {
+        favorites::FavoritesCommand::Add {
+            pattern,
+            regex,
+            mensa,
+        } => {
+            let favorite = favorites::Favorite::new(pattern, regex, mensa)?;
+            registered.retain(|f| f.pattern != favorite.pattern);
+            registered.push(favorite);
+            favorites::save(&registered).map(|_| None)
+        }
+        favorites::FavoritesCommand::Remove { pattern } => {
+            registered.retain(|f| f.pattern != pattern);
+            favorites::save(&registered).map(|_| None)
+        }
+        favorites::FavoritesCommand::List => {
+            if print {
+                for favorite in &registered {
+                    println!("{}", favorite);
+                }
+            }
+            Ok(Some(registered.len()))
+        }
+        favorites::FavoritesCommand::Check { mensa } => {
+            let (sightings, warnings) = favorites::check(&registered, &mensa)?;
+            if print {
+                for sighting in &sightings {
+                    print!("{}", sighting);
+                }
+                print_warnings(&warnings);
+            }
+            Ok(Some(sightings.len()))
+        }
     }
 }
@@ -3,20 +3,128 @@
 use menstruation::{
     allergens::{self, Allergen},
     codes::{self, Mensa},
-    menu::{self, Meal},
+    dates,
+    geo::{Coordinates, Nearby},
+    i18n::{self, Language, MenuLanguage},
+    menu::{self, Meal, Menu},
+    plan::{self, Plan},
+    search::{self, Hit},
+    tray::{self, Tray},
     Group, Response,
 };
-use rocket::{fairing::AdHoc, get, http::Header, routes};
+use rocket::{
+    config::ConfigError,
+    fairing::AdHoc,
+    get,
+    http::Header,
+    response::{self, status::BadRequest, Responder},
+    routes, Request,
+};
 use rocket_contrib::json::Json;
 
+/// A response with warnings about incomplete results, which are sent as `Warning` headers.
+struct Warned<R>(R, Vec<String>);
+
+impl<'r, R: Responder<'r>> Responder<'r> for Warned<R> {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let mut response = self.0.respond_to(request)?;
+        for warning in self.1 {
+            let warning = warning.replace('"', "'");
+            response.adjoin_header(Header::new("Warning", format!("199 - \"{}\"", warning)));
+        }
+        Ok(response)
+    }
+}
+
+/// The bare meal groups of a menu, as before closed days and notices were reported.
 #[get("/menu?<options..>")]
-fn menu(options: menu::MenuOptions) -> Option<Json<Response<Meal>>> {
-    menu::get(options).map(Json).ok()
+fn menu(
+    language: MenuLanguage,
+    options: Result<menu::MenuOptions, String>,
+) -> Result<Option<Json<Response<Meal>>>, BadRequest<String>> {
+    options
+        .map(|options| {
+            menu::get(options, language.0)
+                .map(|menu| Json(menu.groups))
+                .ok()
+        })
+        .map_err(|e| BadRequest(Some(e)))
+}
+
+#[get("/v2/menu?<options..>")]
+fn menu_v2(
+    language: MenuLanguage,
+    options: Result<menu::MenuOptions, String>,
+) -> Result<Option<Json<Menu>>, BadRequest<String>> {
+    options
+        .map(|options| menu::get(options, language.0).map(Json).ok())
+        .map_err(|e| BadRequest(Some(e)))
+}
+
+#[get("/tray?<options..>")]
+fn tray(
+    language: MenuLanguage,
+    options: Result<tray::TrayOptions, String>,
+) -> Result<Option<Json<Vec<Tray>>>, BadRequest<String>> {
+    options
+        .map(|options| tray::get(options, language.0).map(Json).ok())
+        .map_err(|e| BadRequest(Some(e)))
+}
+
+#[get("/plan?<options..>")]
+fn plan(
+    language: MenuLanguage,
+    options: Result<plan::PlanOptions, String>,
+) -> Result<Option<Json<Plan>>, BadRequest<String>> {
+    options
+        .map(|options| plan::get(options, language.0).map(Json).ok())
+        .map_err(|e| BadRequest(Some(e)))
 }
 
-#[get("/codes?<pattern>")]
-fn codes(pattern: Option<String>) -> Option<Json<Response<Mensa>>> {
-    codes::get(pattern).map(Json).ok()
+/// The facilities grouped by university, as before the registry listed every facility once.
+#[get("/codes?<pattern>&<university>")]
+fn codes(pattern: Option<String>, university: Option<String>) -> Option<Json<Response<Mensa>>> {
+    codes::get(pattern, university)
+        .map(|mensas| Json(codes::group_by_university(mensas)))
+        .ok()
+}
+
+#[get("/v2/codes?<pattern>&<university>")]
+fn codes_v2(pattern: Option<String>, university: Option<String>) -> Option<Json<Vec<Mensa>>> {
+    codes::get(pattern, university).map(Json).ok()
+}
+
+#[get("/codes/<mensa>")]
+fn mensa(mensa: String) -> Option<Json<Mensa>> {
+    codes::resolve(&mensa)
+        .ok()
+        .and_then(|code| codes::lookup(&code))
+        .map(Json)
+}
+
+#[get("/codes/nearest?<lat>&<lon>&<limit>")]
+fn nearest(
+    lat: String,
+    lon: String,
+    limit: Option<usize>,
+) -> Result<Option<Json<Vec<Nearby>>>, BadRequest<String>> {
+    let position = format!("{},{}", lat, lon)
+        .parse::<Coordinates>()
+        .map_err(|e| BadRequest(Some(e)))?;
+    Ok(codes::nearest(&position, limit.unwrap_or(5)).map(Json).ok())
+}
+
+#[get("/search?<options..>")]
+fn search(
+    options: Result<search::SearchOptions, String>,
+) -> Result<Option<Warned<Json<Vec<Hit>>>>, BadRequest<String>> {
+    options
+        .map(|options| {
+            search::get(options)
+                .map(|(hits, warnings)| Warned(Json(hits), warnings))
+                .ok()
+        })
+        .map_err(|e| BadRequest(Some(e)))
 }
 
 #[get("/allergens")]
@@ -26,9 +134,40 @@ fn allergens() -> Option<Json<Group<Allergen>>> {
 
 fn main() {
     rocket::ignite()
+        .attach(AdHoc::on_attach("Rollover", |rocket| {
+            // e.g. `rollover = "15:00"` in Rocket.toml or ROCKET_ROLLOVER=15:00
+            let config = rocket.config();
+            let rollover = match config.get_str("rollover") {
+                Ok(time) => dates::parse_time(time).map(Some),
+                Err(ConfigError::Missing(_)) => Ok(None),
+                // a full hour such as ROCKET_ROLLOVER=15 is read as an integer
+                Err(ConfigError::BadType(..)) => config
+                    .get_int("rollover")
+                    .map_err(|e| e.to_string())
+                    .and_then(|hour| dates::parse_time(&hour.to_string()))
+                    .map(Some),
+                Err(e) => Err(e.to_string()),
+            };
+            match rollover {
+                Ok(rollover) => {
+                    dates::set_rollover(rollover);
+                    Ok(rocket)
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::on_request("Language", |request, _| {
+            i18n::set_language(Language::of_request(request));
+        }))
         .attach(AdHoc::on_response("CORS", |_, response| {
             response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
         }))
-        .mount("/", routes![menu, codes, allergens])
+        .mount(
+            "/",
+            routes![menu, menu_v2, tray, plan, codes, codes_v2, mensa, nearest, search, allergens],
+        )
         .launch();
 }
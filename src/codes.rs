@@ -1,8 +1,16 @@
-use super::{error::Error, Group, MensaCode, Response};
+use super::{
+    error::Error,
+    fuzzy,
+    geo::{Coordinates, Nearby},
+    hours::{self, OpeningHours},
+    i18n::{self, Text},
+    Group, MensaCode, Response,
+};
 use ansi_term::{Color, Style};
 use regex::Regex;
 use scraper::{html::Html, ElementRef, Selector};
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -35,15 +43,27 @@ impl TryFrom<ElementRef<'_>> for Group<Mensa> {
         html.select(&mensa_selector)
             .map(Mensa::try_from)
             .collect::<Result<Vec<_>, _>>()
-            .map(|items| Group { name, items })
+            .map(|items| Group {
+                name,
+                course: None,
+                items,
+            })
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Mensa {
-    code: MensaCode,
+    pub code: MensaCode,
     pub name: String,
-    address: String,
+    pub address: String,
+    #[serde(default)]
+    pub universities: Vec<String>,
+    #[serde(default)]
+    pub opening_hours: Option<OpeningHours>,
+    #[serde(default)]
+    pub coordinates: Option<Coordinates>,
+    #[serde(default)]
+    pub slugs: Vec<String>,
 }
 
 impl Display for Mensa {
@@ -69,7 +89,7 @@ impl TryFrom<ElementRef<'_>> for Mensa {
                 .attr("onclick")
                 .ok_or(Error::Parse("Mensa::code".to_string()))?;
             &in_call
-                .captures(&onclick)
+                .captures(onclick)
                 .ok_or(Error::Parse("Mensa::code".to_string()))?[1]
         })
         .map_err(|e| Error::Parse(format!("Mensa::code\n< {}", e)))?;
@@ -88,24 +108,40 @@ impl TryFrom<ElementRef<'_>> for Mensa {
             .map(|t| t.trim())
             .collect::<Vec<_>>()
             .join(", ");
+        let slugs = slugs(&name);
         Ok(Mensa {
             code,
             name,
             address,
+            universities: Vec::new(),
+            opening_hours: None,
+            coordinates: None,
+            slugs,
         })
     }
 }
 
+/// Derives the URL-friendly identifiers of a facility from its name: the slugified name and,
+/// if the name contains a quoted nickname like „Skyline“, the slugified nickname.
+fn slugs(name: &str) -> Vec<String> {
+    let nickname = Regex::new(r#"[„"“]([^„"“”]+)[“"”]"#).unwrap();
+    let mut slugs = vec![fuzzy::slugify(name)];
+    if let Some(captures) = nickname.captures(name) {
+        slugs.push(fuzzy::slugify(&captures[1]));
+    }
+    slugs
+}
+
 /// Filters the given `Response` by the given predicate, removing empty groups.
 ///
 /// # Arguments
 /// * `predicate` - A function that takes a reference to an item and returns `true` if the item
-/// should be kept.
+///   should be kept.
 /// * `response` - The `Response` to filter.
 ///
 /// # Returns
 /// * A new `Response` containing only the items that match the predicate, with empty groups
-/// removed.
+///   removed.
 pub fn filter_response<A>(predicate: impl Fn(&A) -> bool, response: Response<A>) -> Response<A> {
     Response(
         response
@@ -119,6 +155,7 @@ pub fn filter_response<A>(predicate: impl Fn(&A) -> bool, response: Response<A>)
                     .collect::<Vec<_>>();
                 Group {
                     name: group.name,
+                    course: group.course,
                     items,
                 }
             })
@@ -127,26 +164,228 @@ pub fn filter_response<A>(predicate: impl Fn(&A) -> bool, response: Response<A>)
     )
 }
 
-pub fn get(pattern: Option<String>) -> Result<Response<Mensa>, Error> {
-    let codes = serde_json::from_str::<Response<Mensa>>(CODES_DATA)
-        .map_err(|e| Error::Parse(format!("Allergens\n< {}", e)))?;
+/// Loads the bundled facility registry, in which every facility appears once together with the
+/// universities it serves.
+pub fn registry() -> Result<BTreeMap<MensaCode, Mensa>, Error> {
+    serde_json::from_str::<Vec<Mensa>>(CODES_DATA)
+        .map(|mensas| {
+            mensas
+                .into_iter()
+                .map(|mensa| (mensa.code.clone(), mensa))
+                .collect()
+        })
+        .map_err(|e| Error::Parse(format!("Codes\n< {}", e)))
+}
+
+/// Merges a scraped `Response<Mensa>`, which lists a facility once for every university it
+/// serves, into one entry per facility.
+pub fn deduplicate(response: Response<Mensa>) -> Vec<Mensa> {
+    let mut registry: BTreeMap<MensaCode, Mensa> = BTreeMap::new();
+    for group in response.0 {
+        for mensa in group.items {
+            registry
+                .entry(mensa.code.clone())
+                .or_insert(mensa)
+                .universities
+                .push(group.name.clone());
+        }
+    }
+    registry.into_values().collect()
+}
+
+/// Lists the facilities once for every university they serve, in groups named after the
+/// universities, as `/codes` returned them before the registry. The groups keep the order of
+/// their first facility, so that the best matches of a pattern still come first.
+pub fn group_by_university(mensas: Vec<Mensa>) -> Response<Mensa> {
+    let mut groups: Vec<Group<Mensa>> = Vec::new();
+    for mensa in mensas {
+        for university in &mensa.universities {
+            match groups.iter_mut().find(|group| &group.name == university) {
+                Some(group) => group.items.push(mensa.clone()),
+                None => groups.push(Group {
+                    name: university.clone(),
+                    course: None,
+                    items: vec![mensa.clone()],
+                }),
+            }
+        }
+    }
+    Response(groups)
+}
+
+fn serves(mensa: &Mensa, university: &str) -> bool {
+    mensa.universities.iter().any(|name| {
+        fuzzy::score(university, &[(name, 1.0), (&fuzzy::acronym(name), 1.0)]).is_some()
+    })
+}
+
+/// Lists all dining facilities, or, given a pattern, those fuzzily matching it by name, address
+/// or university, best matches first. Optionally only lists the facilities serving a university.
+pub fn get(pattern: Option<String>, university: Option<String>) -> Result<Vec<Mensa>, Error> {
+    let mensas = registry()?
+        .into_values()
+        .filter(|mensa| university.as_ref().map_or(true, |u| serves(mensa, u)));
 
     if let Some(p) = &pattern {
-        return Ok(filter_response(
-            |mensa| {
-                let name_matches = mensa
-                    .name
-                    .to_lowercase()
-                    .contains(&p.to_lowercase());
-                let address_matches = mensa
-                    .address
-                    .to_lowercase()
-                    .contains(&p.to_lowercase());
-                name_matches || address_matches
-            },
-            codes,
-        ));
+        let mut scored = mensas
+            .filter_map(|mensa| {
+                let universities = mensa.universities.join(" ");
+                let acronyms = mensa
+                    .universities
+                    .iter()
+                    .map(|name| fuzzy::acronym(name))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                fuzzy::score(
+                    p,
+                    &[
+                        (&mensa.name, 1.0),
+                        (&universities, 0.9),
+                        (&acronyms, 0.9),
+                        (&mensa.address, 0.8),
+                    ],
+                )
+                .map(|score| (score, mensa))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        Ok(scored.into_iter().map(|(_, mensa)| mensa).collect())
     } else {
-        return Ok(codes);
+        let mut mensas = mensas.collect::<Vec<_>>();
+        mensas.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(mensas)
+    }
+}
+
+/// Resolves a numeric code, a slug or a search pattern to a single dining facility. Fails with
+/// the list of candidates if the pattern matches several facilities.
+pub fn resolve(query: &str) -> Result<MensaCode, Error> {
+    if let Ok(code) = MensaCode::from_str(query) {
+        return Ok(code);
+    }
+    if let Some(mensa) = registry()?
+        .into_values()
+        .find(|mensa| mensa.slugs.iter().any(|slug| slug == query))
+    {
+        return Ok(mensa.code);
+    }
+    let mut candidates = get(Some(query.to_string()), None)?;
+    match candidates.len() {
+        0 => Err(Error::Input(i18n::format(
+            Text::NoFacilityMatches,
+            &[&query],
+        ))),
+        1 => Ok(candidates.remove(0).code),
+        _ => {
+            let listing = candidates
+                .iter()
+                .map(|mensa| {
+                    format!(
+                        "{:>4} {} ({})",
+                        mensa.code.to_string(),
+                        mensa.name,
+                        mensa.slugs.join(", ")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Err(Error::Input(i18n::format(
+                Text::SeveralFacilitiesMatch,
+                &[&query, &listing],
+            )))
+        }
+    }
+}
+
+/// Resolves a dining facility like [`resolve`], for parsing arguments and query parameters, with
+/// the bare error message.
+pub fn parse(query: &str) -> Result<MensaCode, String> {
+    resolve(query).map_err(|e| e.message().to_string())
+}
+
+pub fn lookup(code: &MensaCode) -> Option<Mensa> {
+    registry().ok()?.remove(code)
+}
+
+/// Scrapes the opening hours of every bundled facility from stw.berlin, e.g. to update
+/// `data/codes.json`. Facilities whose hours cannot be scraped keep their bundled hours and are
+/// reported in the warnings.
+pub fn scrape_opening_hours() -> Result<(Vec<Mensa>, Vec<String>), Error> {
+    let mut warnings = Vec::new();
+    let mensas = registry()?
+        .into_values()
+        .map(|mensa| match hours::get(&mensa.code) {
+            Ok(hours) => Mensa {
+                opening_hours: Some(hours),
+                ..mensa
+            },
+            Err(e) => {
+                warnings.push(format!("{}: {}", mensa.code, e.message()));
+                mensa
+            }
+        })
+        .collect();
+    Ok((mensas, warnings))
+}
+
+/// Lists the dining facilities closest to `position`, sorted by great-circle distance.
+pub fn nearest(position: &Coordinates, limit: usize) -> Result<Vec<Nearby>, Error> {
+    let mut result = registry()?
+        .into_values()
+        .filter_map(|mensa| {
+            let distance = position.distance_to(mensa.coordinates.as_ref()?);
+            Some(Nearby { mensa, distance })
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    result.truncate(limit);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_codes_slugs_and_unique_patterns() {
+        assert_eq!(parse("191"), Ok(MensaCode(191)));
+        assert_eq!(parse("hu-sued"), Ok(MensaCode(367)));
+        assert_eq!(parse("wetterleuchten"), Ok(MensaCode(541)));
+        assert_eq!(parse("oase"), Ok(MensaCode(191)));
+    }
+
+    #[test]
+    fn lists_the_candidates_of_ambiguous_patterns_in_plain_text() {
+        let error = parse("veggie").unwrap_err();
+        assert!(error.contains("323") && error.contains("631"), "{}", error);
+        assert!(!error.contains('\u{1b}'), "{:?}", error);
+        let error = parse("xyzzy").unwrap_err();
+        assert!(error.contains("xyzzy") && !error.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn ranks_the_best_matches_first() {
+        let mensas = get(Some("adlrshof".to_string()), None).unwrap();
+        assert_eq!(
+            mensas.first().map(|mensa| &mensa.code),
+            Some(&MensaCode(191))
+        );
+    }
+
+    #[test]
+    fn groups_facilities_by_university_like_before_the_registry() {
+        let response = group_by_university(get(None, Some("HU".to_string())).unwrap());
+        let value = serde_json::to_value(&response).unwrap();
+        let hu = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|group| group["name"] == "Humboldt-Universität zu Berlin")
+            .unwrap();
+        assert!(hu["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|mensa| mensa["code"] == 191));
     }
 }
@@ -0,0 +1,119 @@
+use serde_derive::{Deserialize, Serialize};
+use std::mem;
+
+/// The words joining the parts of a meal name, in German and English.
+const SEPARATORS: &[&str] = &["mit", "und", "dazu", "with", "and"];
+
+/// A meal name split into its main component and its accompaniments, e.g.
+/// "Gemüsecurry mit Reis und Joghurtdip" into "Gemüsecurry" with "Reis" and "Joghurtdip".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Components {
+    pub main: String,
+    pub accompaniments: Vec<String>,
+}
+
+impl Components {
+    /// Splits a meal name on "mit", "und", "dazu" and commas. Parenthesized remarks such as
+    /// "(mit Ei, ohne Käse)" and shortened compounds such as "Kartoffel- und Gemüsepfanne" are
+    /// kept intact.
+    pub fn parse(name: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0usize;
+        for word in name.split_whitespace() {
+            let is_compound = current.trim_end().ends_with('-');
+            if depth == 0 && !is_compound && SEPARATORS.iter().any(|s| word.eq_ignore_ascii_case(s))
+            {
+                parts.push(mem::take(&mut current));
+                continue;
+            }
+            for c in word.chars() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    ',' if depth == 0 => {
+                        parts.push(mem::take(&mut current));
+                        continue;
+                    }
+                    _ => {}
+                }
+                current.push(c);
+            }
+            current.push(' ');
+        }
+        parts.push(current);
+
+        let mut parts = parts
+            .into_iter()
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty());
+        Components {
+            main: parts.next().unwrap_or_else(|| name.trim().to_string()),
+            accompaniments: parts.collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_upstream_names() {
+        let cases: &[(&str, &str, &[&str])] = &[
+            (
+                "Gemüsecurry mit Reis und Joghurtdip",
+                "Gemüsecurry",
+                &["Reis", "Joghurtdip"],
+            ),
+            (
+                "Kartoffel- und Gemüsepfanne",
+                "Kartoffel- und Gemüsepfanne",
+                &[],
+            ),
+            (
+                "Kartoffel- und Gemüsepfanne mit Kräuterquark",
+                "Kartoffel- und Gemüsepfanne",
+                &["Kräuterquark"],
+            ),
+            (
+                "Hähnchenbrust mit Champignonrahmsauce, Reis",
+                "Hähnchenbrust",
+                &["Champignonrahmsauce", "Reis"],
+            ),
+            (
+                "Spaghetti Bolognese (mit Parmesan, Basilikum)",
+                "Spaghetti Bolognese (mit Parmesan, Basilikum)",
+                &[],
+            ),
+            (
+                "Linsen-Dal dazu Basmatireis",
+                "Linsen-Dal",
+                &["Basmatireis"],
+            ),
+            (
+                "Wiener Schnitzel vom Schwein mit Pommes frites und Ketchup",
+                "Wiener Schnitzel vom Schwein",
+                &["Pommes frites", "Ketchup"],
+            ),
+            (
+                "Chili sin Carne with rice and sour cream",
+                "Chili sin Carne",
+                &["rice", "sour cream"],
+            ),
+            ("Obst- und Gemüsesalat", "Obst- und Gemüsesalat", &[]),
+            ("Brokkoli", "Brokkoli", &[]),
+        ];
+        for (name, main, accompaniments) in cases {
+            assert_eq!(
+                Components::parse(name),
+                Components {
+                    main: main.to_string(),
+                    accompaniments: accompaniments.iter().map(|s| s.to_string()).collect(),
+                },
+                "{}",
+                name
+            );
+        }
+    }
+}
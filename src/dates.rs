@@ -0,0 +1,224 @@
+use super::{
+    holidays,
+    i18n::{self, Text},
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Europe::Berlin;
+use std::sync::RwLock;
+
+/// The time of day after which the next serving day's menu is shown by default.
+static ROLLOVER: RwLock<Option<NaiveTime>> = RwLock::new(None);
+
+pub fn set_rollover(time: Option<NaiveTime>) {
+    *ROLLOVER.write().unwrap() = time;
+}
+
+pub fn rollover() -> Option<NaiveTime> {
+    *ROLLOVER.read().unwrap()
+}
+
+/// The current time in Berlin, where all dining facilities are, regardless of the time zone of
+/// the machine.
+pub fn now() -> NaiveDateTime {
+    Utc::now().with_timezone(&Berlin).naive_local()
+}
+
+/// The current date in Berlin.
+pub fn today() -> NaiveDate {
+    now().date()
+}
+
+/// Whether the dining facilities usually serve meals on `date`, i.e. on weekdays that are no
+/// public holidays.
+pub fn is_serving_day(date: NaiveDate) -> bool {
+    date.weekday() != Weekday::Sat && date.weekday() != Weekday::Sun && holidays::on(date).is_none()
+}
+
+/// The first serving day after `date`.
+pub fn next_serving_day(date: NaiveDate) -> NaiveDate {
+    let mut next = date + Duration::days(1);
+    while !is_serving_day(next) {
+        next += Duration::days(1);
+    }
+    next
+}
+
+/// The date to show when none is chosen: today, or the next serving day once the rollover time
+/// has passed.
+pub fn default_date() -> NaiveDate {
+    let now = now();
+    match rollover() {
+        Some(time) if now.time() >= time => next_serving_day(now.date()),
+        _ => now.date(),
+    }
+}
+
+/// Parses a time of day such as `15:00`, `15.30` or `15`.
+pub fn parse_time(string: &str) -> Result<NaiveTime, String> {
+    let string = string.trim().replace('.', ":");
+    NaiveTime::parse_from_str(&string, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&format!("{}:00", string), "%H:%M"))
+        .map_err(|_| i18n::format(Text::WrongTime, &[&string]))
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" | "mon" | "montag" | "mo" => Weekday::Mon,
+        "tuesday" | "tue" | "dienstag" | "di" => Weekday::Tue,
+        "wednesday" | "wed" | "mittwoch" | "mi" => Weekday::Wed,
+        "thursday" | "thu" | "donnerstag" | "do" => Weekday::Thu,
+        "friday" | "fri" | "freitag" | "fr" => Weekday::Fri,
+        "saturday" | "sat" | "samstag" | "sonnabend" | "sa" => Weekday::Sat,
+        "sunday" | "sun" | "sonntag" | "so" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Adds `days` to `date`, or `None` beyond the range of dates.
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    date.checked_add_signed(Duration::try_days(days)?)
+}
+
+/// The next date on `weekday` that is at least `skip` days after `from`.
+fn next_weekday(from: NaiveDate, weekday: Weekday, skip: i64) -> Option<NaiveDate> {
+    let start = add_days(from, skip)?;
+    let days_ahead = (7 + weekday.num_days_from_monday() as i64
+        - start.weekday().num_days_from_monday() as i64)
+        % 7;
+    add_days(start, days_ahead)
+}
+
+/// Reads a German day and month such as `24.12.`, `24.12.24` or `24.12.2024`. Without a year, the
+/// occurrence closest to `today` is taken, so that `02.01.` means the coming January at the end
+/// of December.
+fn day_month(string: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let mut parts = string.split('.');
+    let day = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    match (parts.next(), parts.next()) {
+        (Some(year), None) if !year.bytes().all(|b| b.is_ascii_digit()) => None,
+        (None, None) | (Some(""), None) => (today.year() - 1..=today.year() + 1)
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+            .min_by_key(|date| (*date - today).num_days().abs()),
+        // "17.05.24" is meant as 2024, not as the year 24
+        (Some(year), None) if year.len() == 2 => {
+            NaiveDate::from_ymd_opt(2000 + year.parse::<i32>().ok()?, month, day)
+        }
+        (Some(year), None) if year.len() == 4 => {
+            NaiveDate::from_ymd_opt(year.parse().ok()?, month, day)
+        }
+        _ => None,
+    }
+}
+
+/// Resolves a date relative to `today`.
+pub fn parse_relative(string: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let string = string.trim().to_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&string, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if string.contains('.') {
+        return day_month(&string, today);
+    }
+    if string.starts_with(&['+', '-'][..]) {
+        return add_days(today, string.parse().ok()?);
+    }
+    match &string[..] {
+        "today" | "heute" => return Some(today),
+        "tomorrow" | "morgen" => return add_days(today, 1),
+        "day after tomorrow" | "übermorgen" | "uebermorgen" => return add_days(today, 2),
+        "yesterday" | "gestern" => return add_days(today, -1),
+        _ => {}
+    }
+    let words = string.split_whitespace().collect::<Vec<_>>();
+    match &words[..] {
+        [day] => next_weekday(today, weekday(day)?, 0),
+        ["next", day] | ["nächsten", day] | ["nächster", day] | ["naechsten", day] => {
+            next_weekday(today, weekday(day)?, 1)
+        }
+        _ => None,
+    }
+}
+
+/// Parses a date as `YYYY-MM-DD`, `DD.MM.` or `DD.MM.YYYY`, as a day offset like `+2`, or as a
+/// word like `today`, `morgen`, `friday`, `next friday` or `nächsten Montag`. A bare weekday means
+/// today or the following one; with `next`, today is skipped. Relative dates are resolved in Berlin.
+pub fn parse(string: &str) -> Result<NaiveDate, String> {
+    parse_relative(string, today()).ok_or_else(|| i18n::format(Text::WrongDate, &[&string]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn takes_the_closest_year_for_day_and_month() {
+        let new_years_eve = date(2024, 12, 31);
+        assert_eq!(
+            parse_relative("02.01.", new_years_eve),
+            Some(date(2025, 1, 2))
+        );
+        assert_eq!(
+            parse_relative("24.12.", new_years_eve),
+            Some(date(2024, 12, 24))
+        );
+        assert_eq!(
+            parse_relative("30.12.", date(2025, 1, 2)),
+            Some(date(2024, 12, 30))
+        );
+        assert_eq!(
+            parse_relative("24.12.2023", new_years_eve),
+            Some(date(2023, 12, 24))
+        );
+        assert_eq!(
+            parse_relative("17.05.24", new_years_eve),
+            Some(date(2024, 5, 17))
+        );
+        assert_eq!(parse_relative("17.05.024", new_years_eve), None);
+    }
+
+    #[test]
+    fn skips_today_only_for_the_next_weekday() {
+        // a Friday
+        let today = date(2024, 5, 17);
+        assert_eq!(parse_relative("freitag", today), Some(today));
+        assert_eq!(
+            parse_relative("next friday", today),
+            Some(date(2024, 5, 24))
+        );
+        assert_eq!(
+            parse_relative("nächsten Montag", today),
+            Some(date(2024, 5, 20))
+        );
+    }
+
+    #[test]
+    fn adds_day_offsets() {
+        let today = date(2024, 5, 17);
+        assert_eq!(parse_relative("+2", today), Some(date(2024, 5, 19)));
+        assert_eq!(parse_relative("-17", today), Some(date(2024, 4, 30)));
+        assert_eq!(parse_relative("+99999999999", today), None);
+        assert_eq!(parse_relative("-9223372036854775808", today), None);
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        let today = date(2024, 5, 17);
+        for invalid in &[
+            "",
+            "31.02.",
+            "32.1.",
+            "1.2.3.4",
+            "someday",
+            "next",
+            "+",
+            "2024-13-01",
+        ] {
+            assert_eq!(parse_relative(invalid, today), None, "{}", invalid);
+        }
+    }
+}
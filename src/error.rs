@@ -1,3 +1,4 @@
+use super::i18n::{self, Text};
 use ansi_term::Colour::Red;
 use std::fmt::{Display, Formatter};
 
@@ -5,14 +6,60 @@ use std::fmt::{Display, Formatter};
 pub enum Error {
     Parse(String),
     Net(String),
+    Io(String),
+    Input(String),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         let error_style = Red.bold();
         match self {
-            Error::Parse(message) => write!(f, "{} {}", error_style.paint("PARSE ERROR"), message),
-            Error::Net(message) => write!(f, "{} {}", error_style.paint("NETWORK ERROR"), message),
+            Error::Parse(message) => write!(
+                f,
+                "{} {}",
+                error_style.paint(i18n::text(Text::ParseError)),
+                message
+            ),
+            Error::Net(message) => write!(
+                f,
+                "{} {}",
+                error_style.paint(i18n::text(Text::NetworkError)),
+                message
+            ),
+            Error::Io(message) => write!(
+                f,
+                "{} {}",
+                error_style.paint(i18n::text(Text::IoError)),
+                message
+            ),
+            Error::Input(message) => write!(
+                f,
+                "{} {}",
+                error_style.paint(i18n::text(Text::InputError)),
+                message
+            ),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the bare message without the colored error kind, e.g. for HTTP responses.
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Parse(message)
+            | Error::Net(message)
+            | Error::Io(message)
+            | Error::Input(message) => message,
+        }
+    }
+
+    /// Returns the exit code of the command line interface for this kind of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Input(_) => 2,
+            Error::Net(_) => 3,
+            Error::Parse(_) => 4,
+            Error::Io(_) => 5,
         }
     }
 }
@@ -0,0 +1,328 @@
+use super::{
+    codes, dates,
+    error::Error,
+    i18n::{self, Language, Text},
+    menu::{self, Meal},
+    MensaCode,
+};
+use ansi_term::{Color, Style};
+use chrono::{Datelike, Duration, NaiveDate};
+use regex::{Regex, RegexBuilder};
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A favorite dish. The pattern is compiled once, when the favorite is created or loaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "StoredFavorite", into = "StoredFavorite")]
+pub struct Favorite {
+    pub pattern: String,
+    pub regex: bool,
+    pub mensas: Vec<MensaCode>,
+    matcher: Regex,
+}
+
+/// A favorite as it is saved in the configuration file.
+#[derive(Clone, Serialize, Deserialize)]
+struct StoredFavorite {
+    pattern: String,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default)]
+    mensas: Vec<MensaCode>,
+}
+
+impl TryFrom<StoredFavorite> for Favorite {
+    type Error = Error;
+    fn try_from(stored: StoredFavorite) -> Result<Self, Self::Error> {
+        Favorite::new(stored.pattern, stored.regex, stored.mensas)
+    }
+}
+
+impl From<Favorite> for StoredFavorite {
+    fn from(favorite: Favorite) -> Self {
+        StoredFavorite {
+            pattern: favorite.pattern,
+            regex: favorite.regex,
+            mensas: favorite.mensas,
+        }
+    }
+}
+
+impl PartialEq for Favorite {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.regex == other.regex && self.mensas == other.mensas
+    }
+}
+
+impl Favorite {
+    pub fn new(pattern: String, regex: bool, mensas: Vec<MensaCode>) -> Result<Self, Error> {
+        let escaped = if regex {
+            pattern.clone()
+        } else {
+            regex::escape(&pattern)
+        };
+        let matcher = RegexBuilder::new(&escaped)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| Error::Parse(format!("Favorite::pattern\n< {}", e)))?;
+        Ok(Favorite {
+            pattern,
+            regex,
+            mensas,
+            matcher,
+        })
+    }
+
+    pub fn matches(&self, mensa: &MensaCode, meal: &Meal) -> bool {
+        let mensa_ok = self.mensas.is_empty() || self.mensas.contains(mensa);
+        mensa_ok
+            && (self.matcher.is_match(&meal.name)
+                || meal.names.as_ref().is_some_and(|names| {
+                    self.matcher.is_match(&names.de) || self.matcher.is_match(&names.en)
+                }))
+    }
+}
+
+impl FromStr for Favorite {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.len() > 1 && string.starts_with('/') && string.ends_with('/') {
+            Favorite::new(string[1..string.len() - 1].to_string(), true, Vec::new())
+        } else {
+            Favorite::new(string.to_string(), false, Vec::new())
+        }
+        .map_err(|e| e.message().to_string())
+    }
+}
+
+impl Display for Favorite {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let pattern = if self.regex {
+            format!("/{}/", self.pattern)
+        } else {
+            self.pattern.clone()
+        };
+        write!(f, "{}", Style::new().bold().paint(pattern))?;
+        if !self.mensas.is_empty() {
+            write!(
+                f,
+                " {}",
+                Color::Green.paint(
+                    self.mensas
+                        .iter()
+                        .map(|code| code.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum FavoritesCommand {
+    #[structopt(about = i18n::text(Text::HelpFavoriteAdd))]
+    Add {
+        #[structopt(name = "PATTERN", help = i18n::text(Text::HelpQuery))]
+        pattern: String,
+        #[structopt(short, long, help = i18n::text(Text::HelpRegex))]
+        regex: bool,
+        #[structopt(
+            short,
+            long,
+            parse(try_from_str = codes::parse),
+            help = i18n::text(Text::HelpFavoriteMensas)
+        )]
+        mensa: Vec<MensaCode>,
+    },
+    #[structopt(about = i18n::text(Text::HelpFavoriteRemove))]
+    Remove {
+        #[structopt(name = "PATTERN")]
+        pattern: String,
+    },
+    #[structopt(about = i18n::text(Text::HelpFavoriteList))]
+    List,
+    #[structopt(about = i18n::text(Text::HelpFavoriteCheck))]
+    Check {
+        #[structopt(
+            short,
+            long,
+            default_value = "191",
+            parse(try_from_str = codes::parse),
+            help = i18n::text(Text::HelpCheckMensas)
+        )]
+        mensa: Vec<MensaCode>,
+    },
+}
+
+#[derive(Serialize)]
+pub struct Sighting {
+    pub date: NaiveDate,
+    pub mensa: MensaCode,
+    pub mensa_name: Option<String>,
+    pub meal: Meal,
+}
+
+impl Display for Sighting {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            Style::new().bold().paint(format!(
+                "{} {}",
+                i18n::weekday(self.date.weekday()),
+                self.date.format("%Y-%m-%d")
+            )),
+            Color::Green.paint(format!("{:>4}", self.mensa.to_string())),
+            Style::new()
+                .italic()
+                .paint(self.mensa_name.as_deref().unwrap_or("")),
+            self.meal
+        )
+    }
+}
+
+fn storage_path() -> Result<PathBuf, Error> {
+    dirs::config_dir()
+        .map(|directory| directory.join("menstruation").join("favorites.json"))
+        .ok_or_else(|| Error::Io(i18n::text(Text::NoConfigurationDirectory).to_string()))
+}
+
+pub fn load() -> Result<Vec<Favorite>, Error> {
+    let path = storage_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&path).map_err(|e| Error::Io(e.to_string()))?;
+    serde_json::from_str(&contents).map_err(|e| Error::Parse(format!("Favorites\n< {}", e)))
+}
+
+pub fn save(favorites: &[Favorite]) -> Result<(), Error> {
+    let path = storage_path()?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|e| Error::Io(e.to_string()))?;
+    }
+    let contents = serde_json::to_string_pretty(favorites)
+        .map_err(|e| Error::Parse(format!("Favorites\n< {}", e)))?;
+    fs::write(&path, contents).map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Returns the serving days among the seven days starting at `start`.
+pub(crate) fn upcoming_week(start: NaiveDate) -> Vec<NaiveDate> {
+    (0..7)
+        .map(|offset| start + Duration::days(offset))
+        .filter(|date| dates::is_serving_day(*date))
+        .collect()
+}
+
+/// Scans the upcoming week for favorite dishes. Menus that cannot be fetched are skipped and
+/// reported as warnings; only if no menu could be fetched at all, the first error is returned.
+pub fn check(
+    favorites: &[Favorite],
+    default_mensas: &[MensaCode],
+) -> Result<(Vec<Sighting>, Vec<String>), Error> {
+    let mut mensas: Vec<MensaCode> = Vec::new();
+    for favorite in favorites {
+        let restriction = if favorite.mensas.is_empty() {
+            default_mensas
+        } else {
+            &favorite.mensas
+        };
+        for mensa in restriction {
+            if !mensas.contains(mensa) {
+                mensas.push(mensa.clone());
+            }
+        }
+    }
+
+    let mut sightings = Vec::new();
+    let mut warnings = Vec::new();
+    let mut first_error = None;
+    let mut fetched = false;
+    for date in upcoming_week(dates::default_date()) {
+        for mensa in &mensas {
+            let menu = match menu::fetch(mensa, date, Language::De) {
+                Ok(menu) => menu,
+                Err(e) => {
+                    warnings.push(i18n::format(
+                        Text::FetchFailed,
+                        &[mensa, &date.format("%Y-%m-%d"), &e.message()],
+                    ));
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            fetched = true;
+            for group in menu.groups.0 {
+                for meal in group.items {
+                    let is_favorite = favorites.iter().any(|favorite| {
+                        (!favorite.mensas.is_empty() || default_mensas.contains(mensa))
+                            && favorite.matches(mensa, &meal)
+                    });
+                    if is_favorite {
+                        sightings.push(Sighting {
+                            date,
+                            mensa: mensa.clone(),
+                            mensa_name: codes::lookup(mensa).map(|m| m.name),
+                            meal: Meal {
+                                favorite: true,
+                                ..meal
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    match first_error {
+        Some(e) if !fetched => Err(e),
+        _ => Ok((sightings, warnings)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meal(name: &str) -> Meal {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "color": "green",
+            "tags": [],
+            "co2_rating": null,
+            "water_rating": null,
+            "price": null,
+            "allergens": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_text_case_insensitively_and_literally() {
+        let oase = MensaCode(191);
+        let favorite = "chili con carne".parse::<Favorite>().unwrap();
+        assert!(favorite.matches(&oase, &meal("Chili con Carne mit Reis")));
+        assert!(!favorite.matches(&oase, &meal("Chili sin Carne")));
+        // without slashes, regex syntax is taken literally
+        let favorite = "bowl$".parse::<Favorite>().unwrap();
+        assert!(favorite.matches(&oase, &meal("Die Bowl$ des Tages")));
+        assert!(!favorite.matches(&oase, &meal("Buddha Bowl")));
+    }
+
+    #[test]
+    fn matches_regex_patterns_and_mensas() {
+        let favorite = "/bowl$/".parse::<Favorite>().unwrap();
+        assert!(favorite.regex);
+        assert!(favorite.matches(&MensaCode(191), &meal("Buddha Bowl")));
+        assert!(!favorite.matches(&MensaCode(191), &meal("Bowl mit Tofu")));
+        let favorite = Favorite::new("bowl".to_string(), false, vec![MensaCode(191)]).unwrap();
+        assert!(!favorite.matches(&MensaCode(321), &meal("Buddha Bowl")));
+        assert!("/(/".parse::<Favorite>().is_err());
+    }
+}
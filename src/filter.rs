@@ -0,0 +1,543 @@
+use super::i18n::{self, Text};
+use super::menu::{Cents, Color, Meal, Rating, Tag};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// A boolean expression over meals, e.g.
+/// `(vegan or vegetarian) and price.student < 3.00 and not allergen:22a and name ~ "bowl"`.
+/// `main` and `side` compare against the components of the meal name, where `side` holds if any
+/// accompaniment matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Tag(Tag),
+    Color(Color),
+    Allergen(String),
+    Price(PriceField, Operator, Cents),
+    Co2(Operator, Rating),
+    Water(Operator, Rating),
+    Name(Operator, String),
+    Main(Operator, String),
+    Side(Operator, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceField {
+    Student,
+    Employee,
+    Guest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Contains,
+}
+
+impl Operator {
+    /// Checks an ordering against the operator. `~` is only defined for texts, so it never holds
+    /// here; the parser rejects it for other fields.
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessEqual => ordering != Ordering::Greater,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterEqual => ordering != Ordering::Less,
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::Contains => false,
+        }
+    }
+
+    /// Compares texts case-insensitively, where `~` checks for a substring.
+    fn holds_for_text(self, text: &str, pattern: &str) -> bool {
+        let (text, pattern) = (text.to_lowercase(), pattern.to_lowercase());
+        match self {
+            Operator::Contains => text.contains(&pattern),
+            _ => self.holds(text.cmp(&pattern)),
+        }
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, meal: &Meal) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(meal) && right.matches(meal),
+            Filter::Or(left, right) => left.matches(meal) || right.matches(meal),
+            Filter::Not(inner) => !inner.matches(meal),
+            Filter::Tag(tag) => tag.is_satisfied_by(&meal.tags),
+            Filter::Color(color) => &meal.color == color,
+            Filter::Allergen(allergen) => meal
+                .allergens
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(allergen)),
+            Filter::Price(field, operator, limit) => meal.price.as_ref().is_some_and(|price| {
+                let cents = match field {
+                    PriceField::Student => price.student,
+                    PriceField::Employee => price.employee,
+                    PriceField::Guest => price.guest,
+                };
+                operator.holds(cents.cmp(limit))
+            }),
+            Filter::Co2(operator, limit) => meal
+                .co2_rating
+                .is_some_and(|rating| operator.holds(rating.cmp(limit))),
+            Filter::Water(operator, limit) => meal
+                .water_rating
+                .is_some_and(|rating| operator.holds(rating.cmp(limit))),
+            Filter::Name(operator, pattern) => operator.holds_for_text(&meal.name, pattern),
+            Filter::Main(operator, pattern) => {
+                operator.holds_for_text(&meal.components.main, pattern)
+            }
+            Filter::Side(operator, pattern) => meal
+                .components
+                .accompaniments
+                .iter()
+                .any(|side| operator.holds_for_text(side, pattern)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    Operator(Operator),
+    Word(String),
+    Text(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let next = chars.get(i + 1).copied();
+        let token = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '~' => Token::Operator(Operator::Contains),
+            '<' if next == Some('=') => {
+                i += 1;
+                Token::Operator(Operator::LessEqual)
+            }
+            '<' => Token::Operator(Operator::Less),
+            '>' if next == Some('=') => {
+                i += 1;
+                Token::Operator(Operator::GreaterEqual)
+            }
+            '>' => Token::Operator(Operator::Greater),
+            '=' => {
+                if next == Some('=') {
+                    i += 1;
+                }
+                Token::Operator(Operator::Equal)
+            }
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::Operator(Operator::NotEqual)
+            }
+            '"' | '\'' => {
+                let quote = chars[i];
+                let mut text = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    text.push(chars[i]);
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(i18n::format(Text::UnterminatedString, &[&start]));
+                }
+                Token::Text(text)
+            }
+            c if c.is_alphanumeric() || "_-.,:".contains(c) => {
+                let mut word = String::new();
+                while i < chars.len() && (chars[i].is_alphanumeric() || "_-.,:".contains(chars[i]))
+                {
+                    word.push(chars[i]);
+                    i += 1;
+                }
+                tokens.push((start, Token::Word(word)));
+                continue;
+            }
+            c => {
+                return Err(i18n::format(
+                    Text::UnexpectedCharacter,
+                    &[&format!("{:?}", c), &start],
+                ))
+            }
+        };
+        tokens.push((start, token));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+/// How deeply parentheses and `not` may be nested, so that untrusted filters cannot overflow the
+/// stack of the recursive parser and of the evaluation.
+const MAX_NESTING: usize = 32;
+/// How many tokens a filter may have, which also bounds the depth of long `and`/`or` chains.
+const MAX_TOKENS: usize = 512;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error<T>(&self, expected: Text) -> Result<T, String> {
+        let expected = i18n::text(expected);
+        match self.peek() {
+            Some(_) => Err(i18n::format(
+                Text::ExpectedAtPosition,
+                &[&expected, &self.offset()],
+            )),
+            None => Err(i18n::format(Text::ExpectedAtEnd, &[&expected])),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses one level deeper, within parentheses or after `not`.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Filter, String>,
+    ) -> Result<Filter, String> {
+        if self.nesting >= MAX_NESTING {
+            return Err(i18n::format(
+                Text::NestedTooDeeply,
+                &[&MAX_NESTING, &self.offset()],
+            ));
+        }
+        self.nesting += 1;
+        let filter = parse(self);
+        self.nesting -= 1;
+        filter
+    }
+
+    fn disjunction(&mut self) -> Result<Filter, String> {
+        let mut left = self.conjunction()?;
+        while self.keyword("or") {
+            left = Filter::Or(Box::new(left), Box::new(self.conjunction()?));
+        }
+        Ok(left)
+    }
+
+    fn conjunction(&mut self) -> Result<Filter, String> {
+        let mut left = self.negation()?;
+        while self.keyword("and") {
+            left = Filter::And(Box::new(left), Box::new(self.negation()?));
+        }
+        Ok(left)
+    }
+
+    fn negation(&mut self) -> Result<Filter, String> {
+        if self.keyword("not") {
+            Ok(Filter::Not(Box::new(self.nested(Self::negation)?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Filter, String> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let inner = self.nested(Self::disjunction)?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(inner),
+                    _ => {
+                        self.position -= 1;
+                        self.error(Text::ClosingParenthesis)
+                    }
+                }
+            }
+            Some(Token::Word(word)) => {
+                if let Some(Token::Operator(operator)) = self.peek().cloned() {
+                    self.position += 1;
+                    self.comparison(&word.to_lowercase(), operator, offset)
+                } else {
+                    predicate(&word).map_err(|e| i18n::format(Text::AtPosition, &[&e, &offset]))
+                }
+            }
+            _ => {
+                self.position -= 1;
+                self.error(Text::PredicateOrComparison)
+            }
+        }
+    }
+
+    fn comparison(
+        &mut self,
+        field: &str,
+        operator: Operator,
+        offset: usize,
+    ) -> Result<Filter, String> {
+        let value_offset = self.offset();
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Text(value)) => value,
+            _ => {
+                self.position -= 1;
+                return self.error(Text::Value);
+            }
+        };
+        let at_value = |e: String| i18n::format(Text::AtPosition, &[&e, &value_offset]);
+        let price_field = match field {
+            "price" | "price.student" => Some(PriceField::Student),
+            "price.employee" => Some(PriceField::Employee),
+            "price.guest" => Some(PriceField::Guest),
+            _ => None,
+        };
+        let is_text = field == "name" || field == "main" || field == "side";
+        if operator == Operator::Contains && !is_text {
+            return Err(i18n::format(Text::ContainsOnlyName, &[&field, &offset]));
+        }
+        if let Some(price_field) = price_field {
+            let cents = value.parse::<Cents>().map_err(|_| {
+                at_value(i18n::format(Text::InvalidPrice, &[&format!("{:?}", value)]))
+            })?;
+            return Ok(Filter::Price(price_field, operator, cents));
+        }
+        match field {
+            "co2" => Ok(Filter::Co2(operator, value.parse().map_err(at_value)?)),
+            "water" | "h2o" => Ok(Filter::Water(operator, value.parse().map_err(at_value)?)),
+            "name" => Ok(Filter::Name(operator, value)),
+            "main" => Ok(Filter::Main(operator, value)),
+            "side" => Ok(Filter::Side(operator, value)),
+            _ => Err(i18n::format(Text::UnknownField, &[&field, &offset])),
+        }
+    }
+}
+
+fn predicate(word: &str) -> Result<Filter, String> {
+    let lowercase = word.to_lowercase();
+    let (kind, value) = match lowercase.find(':') {
+        Some(index) => (&lowercase[..index], &lowercase[index + 1..]),
+        None => ("", &lowercase[..]),
+    };
+    let tag = || Tag::from_str(&value.replace(['_', '-'], " "));
+    match kind {
+        "allergen" => Ok(Filter::Allergen(value.to_string())),
+        "tag" => tag().map(Filter::Tag),
+        "color" => Color::from_str(value).map(Filter::Color),
+        "" => tag()
+            .map(Filter::Tag)
+            .or_else(|_| Color::from_str(value).map(Filter::Color))
+            .map_err(|_| i18n::format(Text::UnknownPredicate, &[&word])),
+        _ => Err(i18n::format(Text::UnknownPredicateKind, &[&word])),
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(string)?;
+        if tokens.len() > MAX_TOKENS {
+            return Err(i18n::format(Text::FilterTooLong, &[&MAX_TOKENS]));
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: string.chars().count(),
+            nesting: 0,
+        };
+        let filter = parser.disjunction()?;
+        if parser.peek().is_some() {
+            return parser.error(Text::OperatorOrEnd);
+        }
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Components;
+    use crate::i18n::Language;
+    use crate::menu::Price;
+    use std::collections::HashSet;
+
+    fn meal(name: &str, tags: &[Tag], student: u64) -> Meal {
+        Meal {
+            name: name.to_string(),
+            components: Components::parse(name),
+            color: Color::Green,
+            tags: tags.iter().cloned().collect(),
+            co2_rating: Some(Rating::B),
+            water_rating: None,
+            price: Some(Price {
+                student: student.into(),
+                employee: (student + 100).into(),
+                guest: (student + 200).into(),
+            }),
+            allergens: ["22a".to_string()].iter().cloned().collect::<HashSet<_>>(),
+            nutrition: None,
+            favorite: false,
+            names: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn filter(string: &str) -> Filter {
+        string.parse().unwrap()
+    }
+
+    fn tag(tag: Tag) -> Box<Filter> {
+        Box::new(Filter::Tag(tag))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            filter("vegan or organic and vegetarian"),
+            Filter::Or(
+                tag(Tag::Vegan),
+                Box::new(Filter::And(tag(Tag::Organic), tag(Tag::Vegetarian)))
+            )
+        );
+        assert_eq!(
+            filter("(vegan or organic) and vegetarian"),
+            Filter::And(
+                Box::new(Filter::Or(tag(Tag::Vegan), tag(Tag::Organic))),
+                tag(Tag::Vegetarian)
+            )
+        );
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        assert_eq!(
+            filter("not vegan and organic"),
+            Filter::And(Box::new(Filter::Not(tag(Tag::Vegan))), tag(Tag::Organic))
+        );
+        let curry = meal("Gemüsecurry mit Reis", &[Tag::Vegan], 250);
+        assert!(!filter("not vegan").matches(&curry));
+        assert!(filter("not not vegan").matches(&curry));
+        assert!(filter("not allergen:23").matches(&curry));
+        assert!(!filter("NOT allergen:22A").matches(&curry));
+    }
+
+    #[test]
+    fn vegan_meals_are_vegetarian() {
+        let curry = meal("Gemüsecurry", &[Tag::Vegan], 250);
+        assert!(filter("vegetarian").matches(&curry));
+        assert!(filter("tag:vegetarisch and color:green").matches(&curry));
+    }
+
+    #[test]
+    fn compares_prices_exactly() {
+        let curry = meal("Gemüsecurry", &[], 250);
+        assert!(filter("price <= 2.50").matches(&curry));
+        assert!(filter("price = 2,5").matches(&curry));
+        assert!(!filter("price < 2.50").matches(&curry));
+        assert!(filter("price.employee == 3.50").matches(&curry));
+        assert!(filter("price.guest > 4.49").matches(&curry));
+        assert!(filter("price != 2.49").matches(&curry));
+        assert!(filter("co2 <= B and not co2 < B").matches(&curry));
+        assert!(!filter("water <= E").matches(&curry));
+    }
+
+    #[test]
+    fn matches_quoted_texts() {
+        let bowl = meal("Buddha Bowl mit Tofu und Reis", &[], 390);
+        assert!(filter(r#"name ~ "buddha bowl""#).matches(&bowl));
+        assert!(filter("name ~ 'BOWL'").matches(&bowl));
+        assert!(!filter(r#"name = "bowl""#).matches(&bowl));
+        assert!(filter("main ~ bowl and side ~ reis").matches(&bowl));
+        assert!(!filter("side ~ bowl").matches(&bowl));
+    }
+
+    #[test]
+    fn contains_is_only_defined_for_texts() {
+        i18n::set_language(Language::En);
+        assert_eq!(
+            "price ~ 2".parse::<Filter>(),
+            Err("'~' only applies to name, main and side, not price at position 0".to_string())
+        );
+        let curry = meal("Gemüsecurry", &[], 250);
+        let contains = Filter::Price(PriceField::Student, Operator::Contains, 250.into());
+        assert!(!contains.matches(&curry));
+    }
+
+    #[test]
+    fn reports_errors_with_positions() {
+        i18n::set_language(Language::En);
+        let error = |string: &str| string.parse::<Filter>().unwrap_err();
+        assert_eq!(
+            error(r#"name ~ "bowl"#),
+            "unterminated string starting at position 7"
+        );
+        assert_eq!(
+            error("vegan & organic"),
+            "unexpected character '&' at position 6"
+        );
+        assert_eq!(error("(vegan"), "expected ')' at end of input");
+        assert_eq!(
+            error("vegan organic"),
+            "expected 'and', 'or' or end of input at position 6"
+        );
+        assert_eq!(
+            error("vegan and"),
+            "expected a predicate, a comparison or '(' at end of input"
+        );
+        assert_eq!(
+            error("price < cheap"),
+            "invalid price \"cheap\" at position 8"
+        );
+        assert_eq!(error("spicy"), "unknown predicate spicy at position 0");
+        assert!(error("kcal < 500").starts_with("unknown field kcal at position 0"));
+    }
+
+    #[test]
+    fn limits_the_nesting_depth() {
+        let nested = |depth| format!("{}vegan{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(filter(&nested(MAX_NESTING)), Filter::Tag(Tag::Vegan));
+        assert!(nested(MAX_NESTING + 1).parse::<Filter>().is_err());
+        assert!(nested(50_000).parse::<Filter>().is_err());
+        assert!(format!("{}vegan", "not ".repeat(50_000))
+            .parse::<Filter>()
+            .is_err());
+        assert!(vec!["vegan"; 50_000]
+            .join(" and ")
+            .parse::<Filter>()
+            .is_err());
+    }
+}
@@ -0,0 +1,162 @@
+/// Lowercases the string and folds umlauts, ß and common accents into plain ASCII, so that
+/// "Straße" matches "Strasse" and "Universität" matches "Universitaet".
+pub fn fold(string: &str) -> String {
+    let mut folded = String::with_capacity(string.len());
+    for c in string.to_lowercase().chars() {
+        match c {
+            'ä' => folded.push_str("ae"),
+            'ö' => folded.push_str("oe"),
+            'ü' => folded.push_str("ue"),
+            'ß' => folded.push_str("ss"),
+            'à' | 'á' | 'â' => folded.push('a'),
+            'è' | 'é' | 'ê' => folded.push('e'),
+            'ì' | 'í' | 'î' => folded.push('i'),
+            'ò' | 'ó' | 'ô' => folded.push('o'),
+            'ù' | 'ú' | 'û' => folded.push('u'),
+            _ => folded.push(c),
+        }
+    }
+    folded
+}
+
+pub fn tokens(string: &str) -> Vec<String> {
+    fold(string)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Turns a facility name into a URL-friendly identifier, e.g. "Mensa HU Süd" into "hu-sued".
+pub fn slugify(name: &str) -> String {
+    let tokens = tokens(name);
+    let tokens = match tokens.first() {
+        Some(first) if first == "mensa" && tokens.len() > 1 => &tokens[1..],
+        _ => &tokens[..],
+    };
+    tokens.join("-")
+}
+
+/// Abbreviates a university name by its capitalized words, e.g. "Freie Universität Berlin" to
+/// "FU". The city name is left out since it is part of almost every university name.
+pub fn acronym(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| *word != "Berlin")
+        .filter_map(|word| word.chars().next())
+        .filter(|initial| initial.is_uppercase())
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn token_score(query: &str, target: &str) -> f64 {
+    let length = query.chars().count();
+    let allowed_edits = match length {
+        0..=3 => 0,
+        4..=6 => 1,
+        _ => 2,
+    };
+    if query == target {
+        1.0
+    } else if length >= 3 && target.starts_with(query) {
+        0.9
+    } else if length >= 3 && target.contains(query) {
+        0.8
+    } else {
+        let distance = edit_distance(query, target);
+        if distance <= allowed_edits {
+            0.7 * (1.0 - distance as f64 / length.max(target.chars().count()) as f64)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Scores how well `query` matches the given weighted fields. Every query token has to match
+/// some token of some field; the result is the average of the best weighted token scores, or
+/// `None` if any query token does not match at all.
+pub fn score(query: &str, fields: &[(&str, f64)]) -> Option<f64> {
+    let query_tokens = tokens(query);
+    if query_tokens.is_empty() {
+        return Some(1.0);
+    }
+    let field_tokens = fields
+        .iter()
+        .map(|(field, weight)| (tokens(field), *weight))
+        .collect::<Vec<_>>();
+    let mut total = 0.0;
+    for query_token in &query_tokens {
+        let best = field_tokens
+            .iter()
+            .flat_map(|(targets, weight)| {
+                targets
+                    .iter()
+                    .map(move |target| token_score(query_token, target) * weight)
+            })
+            .fold(0.0, f64::max);
+        if best <= 0.0 {
+            return None;
+        }
+        total += best;
+    }
+    Some(total / query_tokens.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_umlauts_and_sharp_s() {
+        assert_eq!(fold("Straße"), fold("Strasse"));
+        assert_eq!(fold("Universität"), "universitaet");
+        assert_eq!(tokens("Mensa HU Süd"), vec!["mensa", "hu", "sued"]);
+        assert_eq!(slugify("Mensa HU Süd"), "hu-sued");
+    }
+
+    #[test]
+    fn abbreviates_universities() {
+        assert_eq!(acronym("Humboldt-Universität zu Berlin"), "HU");
+        assert_eq!(acronym("Freie Universität Berlin"), "FU");
+    }
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(edit_distance("adlrshof", "adlershof"), 1);
+        assert_eq!(edit_distance("oase", "oase"), 0);
+        assert_eq!(edit_distance("", "hu"), 2);
+    }
+
+    #[test]
+    fn scores_tokens_in_any_order_with_typos() {
+        let fields = [
+            ("Mensa HU Oase Adlershof", 1.0),
+            ("Rudower Chaussee 25", 0.5),
+        ];
+        assert_eq!(score("adlershof oase", &fields), Some(1.0));
+        let typo = score("Adlrshof", &fields).unwrap();
+        assert!(0.0 < typo && typo < 1.0);
+        assert_eq!(score("charlottenburg", &fields), None);
+        let street = [("Hardenbergstraße 34", 1.0)];
+        assert_eq!(
+            score("hardenbergstrasse", &street),
+            score("Hardenbergstraße", &street)
+        );
+        // the university's acronym as one more field
+        let university = [("Mensa Veggie N° 1", 1.0), ("FU", 0.8)];
+        assert_eq!(score("fu", &university), Some(0.8));
+        assert_eq!(score("hu", &university), None);
+    }
+}
@@ -0,0 +1,93 @@
+use super::codes::Mensa;
+use super::i18n::{self, Text};
+use ansi_term::{Color, Style};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// Computes the great-circle distance in kilometres using the haversine formula.
+    pub fn distance_to(&self, other: &Coordinates) -> f64 {
+        let (phi1, phi2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let delta_phi = (other.latitude - self.latitude).to_radians();
+        let delta_lambda = (other.longitude - self.longitude).to_radians();
+        let a = (delta_phi / 2.0).sin().powi(2)
+            + phi1.cos() * phi2.cos() * (delta_lambda / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// Checks that the latitude and longitude are within their ranges.
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+            Some(Coordinates {
+                latitude,
+                longitude,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Coordinates {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = || i18n::format(Text::WrongCoordinates, &[&string]);
+        let mut parts = string.split(',').map(|part| part.trim().parse::<f64>());
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(latitude)), Some(Ok(longitude)), None) => {
+                Coordinates::new(latitude, longitude).ok_or_else(error)
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Nearby {
+    pub mensa: Mensa,
+    /// The great-circle distance in kilometres
+    pub distance: f64,
+}
+
+impl Display for Nearby {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} {} {}",
+            Color::Green.paint(format!("{:>4}", &self.mensa.code.to_string())),
+            Style::new().italic().paint(&self.mensa.name),
+            Style::new()
+                .dimmed()
+                .paint(format!("({} km)", i18n::decimal(self.distance, 1))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_coordinates_within_range() {
+        assert_eq!(
+            "52.52, 13.40".parse(),
+            Ok(Coordinates {
+                latitude: 52.52,
+                longitude: 13.40
+            })
+        );
+        assert!("91,13".parse::<Coordinates>().is_err());
+        assert!("52,181".parse::<Coordinates>().is_err());
+        assert!("NaN,13".parse::<Coordinates>().is_err());
+        assert!("52".parse::<Coordinates>().is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use super::i18n::{self, Text};
+use chrono::{Datelike, NaiveDate};
+use serde_derive::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// The public holidays in Berlin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Holiday {
+    NewYear,
+    WomensDay,
+    GoodFriday,
+    EasterMonday,
+    LabourDay,
+    LiberationDay,
+    AscensionDay,
+    WhitMonday,
+    GermanUnity,
+    ChristmasDay,
+    BoxingDay,
+}
+
+impl Display for Holiday {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            i18n::text(match self {
+                Holiday::NewYear => Text::NewYear,
+                Holiday::WomensDay => Text::WomensDay,
+                Holiday::GoodFriday => Text::GoodFriday,
+                Holiday::EasterMonday => Text::EasterMonday,
+                Holiday::LabourDay => Text::LabourDay,
+                Holiday::LiberationDay => Text::LiberationDay,
+                Holiday::AscensionDay => Text::AscensionDay,
+                Holiday::WhitMonday => Text::WhitMonday,
+                Holiday::GermanUnity => Text::GermanUnity,
+                Holiday::ChristmasDay => Text::ChristmasDay,
+                Holiday::BoxingDay => Text::BoxingDay,
+            })
+        )
+    }
+}
+
+/// Computes Easter Sunday in the Gregorian calendar with the anonymous algorithm (Meeus/Jones/
+/// Butcher).
+pub fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// Returns the Berlin public holiday on `date`, if any.
+pub fn on(date: NaiveDate) -> Option<Holiday> {
+    let year = date.year();
+    let fixed = match (date.month(), date.day()) {
+        (1, 1) => Some(Holiday::NewYear),
+        // a public holiday in Berlin since 2019
+        (3, 8) if year >= 2019 => Some(Holiday::WomensDay),
+        (5, 1) => Some(Holiday::LabourDay),
+        // declared a one-off public holiday for the 75th and 80th anniversaries
+        (5, 8) if year == 2020 || year == 2025 => Some(Holiday::LiberationDay),
+        (10, 3) => Some(Holiday::GermanUnity),
+        (12, 25) => Some(Holiday::ChristmasDay),
+        (12, 26) => Some(Holiday::BoxingDay),
+        _ => None,
+    };
+    fixed.or_else(|| match (date - easter(year)).num_days() {
+        -2 => Some(Holiday::GoodFriday),
+        1 => Some(Holiday::EasterMonday),
+        39 => Some(Holiday::AscensionDay),
+        50 => Some(Holiday::WhitMonday),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn computes_easter_sunday() {
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+        assert_eq!(on(date(2024, 3, 29)), Some(Holiday::GoodFriday));
+        assert_eq!(on(date(2025, 4, 21)), Some(Holiday::EasterMonday));
+        assert_eq!(on(date(2025, 6, 9)), Some(Holiday::WhitMonday));
+    }
+
+    #[test]
+    fn observes_womens_day_from_2019() {
+        assert_eq!(on(date(2018, 3, 8)), None);
+        assert_eq!(on(date(2019, 3, 8)), Some(Holiday::WomensDay));
+        assert_eq!(on(date(2024, 3, 8)), Some(Holiday::WomensDay));
+    }
+}
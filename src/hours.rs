@@ -0,0 +1,268 @@
+use super::{
+    error::Error,
+    i18n::{self, Text},
+    MensaCode,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use regex::Regex;
+use reqwest::{blocking::Client, header};
+use scraper::Html;
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::sync::RwLock;
+
+/// The lecture periods of the current academic years as inclusive date ranges, if configured.
+static LECTURE_PERIODS: RwLock<Vec<(NaiveDate, NaiveDate)>> = RwLock::new(Vec::new());
+
+pub fn set_lecture_periods(periods: Vec<(NaiveDate, NaiveDate)>) {
+    *LECTURE_PERIODS.write().unwrap() = periods;
+}
+
+/// Parses a lecture period such as `2024-10-14..2025-02-15`, as published in the academic
+/// calendars of the universities.
+pub fn parse_lecture_period(string: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let mut bounds = string.trim().splitn(2, "..");
+    let mut bound = || NaiveDate::parse_from_str(bounds.next()?.trim(), "%Y-%m-%d").ok();
+    match (bound(), bound()) {
+        (Some(start), Some(end)) if start <= end => Ok((start, end)),
+        _ => Err(i18n::format(Text::WrongLecturePeriod, &[&string.trim()])),
+    }
+}
+
+/// Parses comma-separated lecture periods.
+pub fn parse_lecture_periods(string: &str) -> Result<Vec<(NaiveDate, NaiveDate)>, String> {
+    string.split(',').map(parse_lecture_period).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Period {
+    #[serde(rename = "lecture")]
+    Lecture,
+    #[serde(rename = "non-lecture")]
+    NonLecture,
+}
+
+impl Period {
+    /// Looks up the configured lecture periods. Without any, the period is approximated by the
+    /// semester framework of the Berlin universities, whose lectures run from mid-April to
+    /// mid-July and from mid-October to mid-February; the exact dates shift from year to year.
+    pub fn at(date: NaiveDate) -> Self {
+        let periods = LECTURE_PERIODS.read().unwrap();
+        let lecture = if periods.is_empty() {
+            let day = (date.month(), date.day());
+            let summer = ((4, 15)..=(7, 20)).contains(&day);
+            let winter = (10, 15) <= day || day <= (2, 15);
+            summer || winter
+        } else {
+            periods
+                .iter()
+                .any(|(start, end)| (start..=end).contains(&&date))
+        };
+        if lecture {
+            Period::Lecture
+        } else {
+            Period::NonLecture
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Opening {
+    pub weekday: Weekday,
+    pub opens: NaiveTime,
+    pub closes: NaiveTime,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpeningHours {
+    pub lecture: Vec<Opening>,
+    pub non_lecture: Vec<Opening>,
+}
+
+impl OpeningHours {
+    pub fn during(&self, period: Period) -> &[Opening] {
+        match period {
+            Period::Lecture => &self.lecture,
+            Period::NonLecture => &self.non_lecture,
+        }
+    }
+
+    pub fn is_open(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        self.during(Period::at(at.date())).iter().any(|opening| {
+            opening.weekday == at.weekday() && opening.opens <= time && time < opening.closes
+        })
+    }
+}
+
+fn weekday_from_german(abbreviation: &str) -> Option<Weekday> {
+    match abbreviation {
+        "Mo" => Some(Weekday::Mon),
+        "Di" => Some(Weekday::Tue),
+        "Mi" => Some(Weekday::Wed),
+        "Do" => Some(Weekday::Thu),
+        "Fr" => Some(Weekday::Fri),
+        "Sa" => Some(Weekday::Sat),
+        "So" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+impl TryFrom<Html> for OpeningHours {
+    type Error = Error;
+    fn try_from(html: Html) -> Result<Self, Self::Error> {
+        let heading_or_opening = Regex::new(
+            r"(?P<heading>(?i:vorlesungsfrei|vorlesungszeit))|(?P<from>Mo|Di|Mi|Do|Fr|Sa|So)[a-z]*\.?\s*(?:[-–]\s*(?P<to>Mo|Di|Mi|Do|Fr|Sa|So)[a-z]*\.?)?\s*:?\s*(?P<opens>\d{1,2}[:.]\d{2})\s*(?:Uhr)?\s*[-–]\s*(?P<closes>\d{1,2}[:.]\d{2})",
+        )
+        .unwrap();
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(&time.replace('.', ":"), "%H:%M")
+                .map_err(|e| Error::Parse(format!("OpeningHours\n< {}", e)))
+        };
+
+        let text = html.root_element().text().collect::<Vec<_>>().join(" ");
+        let mut hours = OpeningHours::default();
+        // opening times listed before any period heading apply to both periods
+        let mut periods = vec![Period::Lecture, Period::NonLecture];
+        for captures in heading_or_opening.captures_iter(&text) {
+            if let Some(heading) = captures.name("heading") {
+                periods = if heading.as_str().to_lowercase() == "vorlesungsfrei" {
+                    vec![Period::NonLecture]
+                } else {
+                    vec![Period::Lecture]
+                };
+                continue;
+            }
+            let from = weekday_from_german(&captures["from"])
+                .ok_or_else(|| Error::Parse("OpeningHours::weekday".to_string()))?;
+            let to = captures
+                .name("to")
+                .and_then(|to| weekday_from_german(to.as_str()))
+                .unwrap_or(from);
+            let opens = parse_time(&captures["opens"])?;
+            let closes = parse_time(&captures["closes"])?;
+            let mut weekday = from;
+            loop {
+                for period in &periods {
+                    let openings = match period {
+                        Period::Lecture => &mut hours.lecture,
+                        Period::NonLecture => &mut hours.non_lecture,
+                    };
+                    openings.push(Opening {
+                        weekday,
+                        opens,
+                        closes,
+                    });
+                }
+                if weekday == to {
+                    break;
+                }
+                weekday = weekday.succ();
+            }
+        }
+        if hours.lecture.is_empty() && hours.non_lecture.is_empty() {
+            Err(Error::Parse(
+                "OpeningHours\n< no opening times found".to_string(),
+            ))
+        } else {
+            Ok(hours)
+        }
+    }
+}
+
+/// Scrapes the opening hours of a dining facility from its page on stw.berlin. This is only
+/// needed to update the bundled registry.
+pub fn get(mensa: &MensaCode) -> Result<OpeningHours, Error> {
+    let text = Client::new()
+        .post("https://www.stw.berlin/xhr/speiseplan-und-standortdaten.html")
+        .form(&[("resources_id", &mensa.0.to_string())])
+        .header(header::USER_AGENT, "Mozilla/5.0")
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|e| Error::Net(e.to_string()))?;
+    OpeningHours::try_from(Html::parse_fragment(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_lecture_periods() {
+        assert_eq!(
+            parse_lecture_periods("2024-10-14..2025-02-15, 2025-04-22..2025-07-26"),
+            Ok(vec![
+                (date(2024, 10, 14), date(2025, 2, 15)),
+                (date(2025, 4, 22), date(2025, 7, 26)),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lecture_periods() {
+        assert!(parse_lecture_period("2024-10-14").is_err());
+        assert!(parse_lecture_period("2025-02-15..2024-10-14").is_err());
+        assert!(parse_lecture_period("14.10.2024..15.02.2025").is_err());
+        assert!(parse_lecture_periods("").is_err());
+    }
+
+    fn hours(html: &str) -> Result<OpeningHours, Error> {
+        OpeningHours::try_from(Html::parse_fragment(html))
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_opening_hours_per_period() {
+        let hours = hours(
+            "<p>Vorlesungszeit</p><p>Mo - Fr 11:00 - 14:30 Uhr</p>\
+             <p>Vorlesungsfreie Zeit</p><p>Mo-Do: 11.30 – 14.00</p>",
+        )
+        .unwrap();
+        let weekdays = |openings: &[Opening]| {
+            openings
+                .iter()
+                .map(|opening| opening.weekday)
+                .collect::<Vec<_>>()
+        };
+        use Weekday::*;
+        assert_eq!(weekdays(&hours.lecture), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(weekdays(&hours.non_lecture), vec![Mon, Tue, Wed, Thu]);
+        assert_eq!(
+            hours.non_lecture[0].opens,
+            NaiveTime::from_hms_opt(11, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn applies_hours_without_heading_to_both_periods() {
+        let hours = hours("<p>Sa 12:00 - 14:00</p>").unwrap();
+        assert_eq!(hours.lecture.len(), 1);
+        assert_eq!(hours.non_lecture.len(), 1);
+        assert!(self::hours("<p>Heute geschlossen</p>").is_err());
+    }
+
+    #[test]
+    fn is_open_within_the_hours_of_the_period() {
+        let hours = hours(
+            "<p>Vorlesungszeit</p><p>Mo - Fr 11:00 - 14:30</p>\
+             <p>Vorlesungsfrei</p><p>Mi 12:00 - 13:00</p>",
+        )
+        .unwrap();
+        // Wednesdays in the approximated lecture and non-lecture periods
+        let lecture = date(2024, 5, 15);
+        let non_lecture = date(2024, 8, 14);
+        assert!(hours.is_open(at(lecture, 11, 0)));
+        assert!(!hours.is_open(at(lecture, 14, 30)));
+        assert!(!hours.is_open(at(date(2024, 5, 18), 12, 0)));
+        assert!(!hours.is_open(at(non_lecture, 11, 30)));
+        assert!(hours.is_open(at(non_lecture, 12, 30)));
+    }
+}
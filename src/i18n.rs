@@ -0,0 +1,802 @@
+use chrono::Weekday;
+#[cfg(feature = "server")]
+use rocket::{
+    request::{self, FromRequest, Request},
+    Outcome,
+};
+use std::cell::Cell;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The language of all user-facing text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    De,
+    En,
+}
+
+impl FromStr for Language {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        // accepts language tags like de-DE or en_GB by their primary subtag
+        let primary = string
+            .split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        match primary.as_str() {
+            "de" | "deutsch" | "german" => Ok(Language::De),
+            "en" | "englisch" | "english" => Ok(Language::En),
+            _ => Err(format(Text::WrongLanguage, &[&string])),
+        }
+    }
+}
+
+impl Language {
+    /// Picks the supported language with the highest quality value from an `Accept-Language`
+    /// header, e.g. `en-US,en;q=0.9,de;q=0.8`.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut candidates = header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let language = parts.next()?.trim().parse::<Language>().ok()?;
+                let quality = parts
+                    .find_map(|parameter| parameter.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f64>().ok())?;
+                Some((quality, language))
+            })
+            .filter(|(quality, _)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        // the sort is stable, so earlier entries win among equal quality values
+        candidates.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        candidates.first().map(|(_, language)| *language)
+    }
+}
+
+thread_local! {
+    static LANGUAGE: Cell<Language> = Cell::new(Language::default());
+}
+
+/// Sets the language for all text produced by the current thread.
+pub fn set_language(language: Language) {
+    LANGUAGE.with(|current| current.set(language));
+}
+
+pub fn language() -> Language {
+    LANGUAGE.with(Cell::get)
+}
+
+#[cfg(feature = "server")]
+impl Language {
+    /// Determines the language of a request from its `lang` query parameter or, failing that, its
+    /// `Accept-Language` header.
+    pub fn of_request(request: &Request) -> Self {
+        request
+            .get_query_value::<String>("lang")
+            .and_then(Result::ok)
+            .and_then(|lang| lang.parse().ok())
+            .or_else(|| {
+                request
+                    .headers()
+                    .get_one("Accept-Language")
+                    .and_then(Language::from_accept_language)
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Makes the language of a request the language of the thread handling it. The server also sets
+/// it for every request in a fairing, since worker threads are reused and routes without this
+/// guard would otherwise inherit the language of an earlier request.
+#[cfg(feature = "server")]
+impl<'a, 'r> FromRequest<'a, 'r> for Language {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let language = Language::of_request(request);
+        set_language(language);
+        Outcome::Success(language)
+    }
+}
+
+/// The language of the menus fetched from stw.berlin for a request. Unlike the language of the
+/// messages, it only changes with an explicit `lang` query parameter, not with the
+/// `Accept-Language` header of a browser.
+#[cfg(feature = "server")]
+pub struct MenuLanguage(pub Language);
+
+#[cfg(feature = "server")]
+impl<'a, 'r> FromRequest<'a, 'r> for MenuLanguage {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let language = request
+            .get_query_value::<String>("lang")
+            .and_then(Result::ok)
+            .and_then(|lang| lang.parse().ok())
+            .unwrap_or_default();
+        Outcome::Success(MenuLanguage(language))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    ParseError,
+    NetworkError,
+    IoError,
+    InputError,
+    Warnings,
+    UnknownColorImage,
+    UnknownTagImage,
+    UnknownRatingImage,
+    NoColor,
+    NoAllergens,
+    FetchFailed,
+    Vegetarian,
+    Vegan,
+    Organic,
+    SustainableFishing,
+    ClimateFriendly,
+    UnknownTag,
+    Starters,
+    Salads,
+    Soups,
+    MainCourses,
+    SideDishes,
+    Desserts,
+    OtherCourses,
+    Fat,
+    Carbohydrates,
+    Protein,
+    Salt,
+    TrayScore,
+    PlanNoMenu,
+    PlanNoMatchingMeal,
+    PlanBudget,
+    PlanVariety,
+    PlanTotal,
+    NoTray,
+    Closed,
+    Weekend,
+    NewYear,
+    WomensDay,
+    GoodFriday,
+    EasterMonday,
+    LabourDay,
+    LiberationDay,
+    AscensionDay,
+    WhitMonday,
+    GermanUnity,
+    ChristmasDay,
+    BoxingDay,
+    TuiMensas,
+    TuiSearch,
+    TuiMenu,
+    TuiNoMeals,
+    TuiDetails,
+    TuiStudents,
+    TuiEmployees,
+    TuiGuests,
+    TuiAllergens,
+    TuiExcludeAllergens,
+    TuiHelp,
+    WrongPrice,
+    WrongColor,
+    WrongTag,
+    WrongRating,
+    WrongCourse,
+    WrongSortKey,
+    WrongCoordinates,
+    WrongLanguage,
+    WrongDate,
+    WrongTime,
+    WrongLecturePeriod,
+    WrongWeight,
+    TooManyCourses,
+    NoFacilityMatches,
+    SeveralFacilitiesMatch,
+    NoConfigurationDirectory,
+    MissingParameter,
+    InvalidFilter,
+    UnterminatedString,
+    UnexpectedCharacter,
+    ExpectedAtPosition,
+    ExpectedAtEnd,
+    AtPosition,
+    ClosingParenthesis,
+    NestedTooDeeply,
+    FilterTooLong,
+    PredicateOrComparison,
+    Value,
+    OperatorOrEnd,
+    ContainsOnlyName,
+    InvalidPrice,
+    UnknownField,
+    UnknownPredicate,
+    UnknownPredicateKind,
+    HelpLang,
+    HelpRollover,
+    HelpQuiet,
+    HelpCount,
+    HelpMenuCommand,
+    HelpCodesCommand,
+    HelpCodesPattern,
+    HelpCodesUniversity,
+    HelpNear,
+    HelpNearLimit,
+    HelpAllergensCommand,
+    HelpFavoritesCommand,
+    HelpSearchCommand,
+    HelpTrayCommand,
+    HelpPlanCommand,
+    HelpTuiCommand,
+    HelpColors,
+    HelpTags,
+    HelpRequireAllTags,
+    HelpExcludeTags,
+    HelpMaxPrice,
+    HelpMaxCo2,
+    HelpMaxWater,
+    HelpMaxKcal,
+    HelpMinProtein,
+    HelpNutrition,
+    HelpCourses,
+    HelpNoSides,
+    HelpSort,
+    HelpAllergens,
+    HelpDate,
+    HelpNextOpen,
+    HelpBilingual,
+    HelpMensa,
+    HelpFilter,
+    HelpFavorites,
+    HelpQuery,
+    HelpSearchUniversity,
+    HelpPriceWeight,
+    HelpColorWeight,
+    HelpCo2Weight,
+    HelpTrayBudget,
+    HelpTrayCourses,
+    HelpTrayLimit,
+    HelpPlanBudget,
+    HelpMaxRed,
+    HelpAllowRepeats,
+    HelpExtraMensas,
+    HelpFavoriteAdd,
+    HelpRegex,
+    HelpFavoriteMensas,
+    HelpFavoriteRemove,
+    HelpFavoriteList,
+    HelpFavoriteCheck,
+    HelpCheckMensas,
+    HelpHeadingUsage,
+    HelpHeadingFlags,
+    HelpHeadingOptions,
+    HelpHeadingArgs,
+    HelpHeadingSubcommands,
+    HelpHelp,
+    HelpVersion,
+    HelpHelpSubcommand,
+    HelpMoreInformation,
+    HelpErrorPrefix,
+    HelpDefault,
+    HelpErrorInvalidValue,
+    HelpErrorFoundArgument,
+    HelpErrorUnexpected,
+    HelpErrorMissingArguments,
+    HelpErrorMissingValue,
+    HelpErrorConflict,
+    HelpErrorArgument,
+    HelpErrorPossibleValue,
+    HelpErrorPossibleValues,
+    HelpErrorSubcommand,
+    HelpErrorUnrecognized,
+    HelpErrorMissingSubcommand,
+    HelpErrorRetry,
+    HelpErrorDidYouMean,
+}
+
+impl Text {
+    fn translations(self) -> (&'static str, &'static str) {
+        match self {
+            Text::ParseError => ("PARSE-FEHLER", "PARSE ERROR"),
+            Text::NetworkError => ("NETZWERKFEHLER", "NETWORK ERROR"),
+            Text::IoError => ("E/A-FEHLER", "IO ERROR"),
+            Text::InputError => ("EINGABEFEHLER", "INPUT ERROR"),
+            Text::Warnings => ("WARNUNGEN", "WARNINGS"),
+            Text::UnknownColorImage => ("unbekannte Farbe für Bild: {}", "unknown color for image: {}"),
+            Text::UnknownTagImage => ("unbekanntes Tag für Bild: {}", "unknown tag for image: {}"),
+            Text::UnknownRatingImage => ("unbekannte Bewertung für Bild: {}", "unknown rating for image: {}"),
+            Text::NoColor => ("keine Ampelfarbe für {}", "no traffic light color for {}"),
+            Text::NoAllergens => ("keine Allergene für {}", "no allergens for {}"),
+            Text::FetchFailed => ("Mensa {} am {} übersprungen: {}", "skipped dining facility {} on {}: {}"),
+            Text::Vegetarian => ("vegetarisch", "vegetarian"),
+            Text::Vegan => ("vegan", "vegan"),
+            Text::Organic => ("bio", "organic"),
+            Text::SustainableFishing => ("nachhaltige Fischerei", "sustainable fishing"),
+            Text::ClimateFriendly => ("klimafreundlich", "climate friendly"),
+            Text::UnknownTag => ("unbekannt", "unknown"),
+            Text::Starters => ("Vorspeisen", "Starters"),
+            Text::Salads => ("Salate", "Salads"),
+            Text::Soups => ("Suppen", "Soups"),
+            Text::MainCourses => ("Essen", "Main courses"),
+            Text::SideDishes => ("Beilagen", "Side dishes"),
+            Text::Desserts => ("Desserts", "Desserts"),
+            Text::OtherCourses => ("Sonstiges", "Other"),
+            Text::Fat => ("Fett", "fat"),
+            Text::Carbohydrates => ("Kohlenhydrate", "carbohydrates"),
+            Text::Protein => ("Eiweiß", "protein"),
+            Text::Salt => ("Salz", "salt"),
+            Text::TrayScore => ("Wertung {}", "score {}"),
+            Text::PlanNoMenu => ("kein Speiseplan veröffentlicht", "no menu published"),
+            Text::PlanNoMatchingMeal => (
+                "kein Gericht passt zu den Filtern",
+                "no meal matches the filters",
+            ),
+            Text::PlanBudget => (
+                "jedes passende Gericht sprengt das Budget",
+                "every matching meal exceeds the budget",
+            ),
+            Text::PlanVariety => (
+                "jedes bezahlbare Gericht verletzt die Abwechslungsregeln",
+                "every affordable meal breaks the variety rules",
+            ),
+            Text::PlanTotal => ("Summe {} von {}", "total {} of {}"),
+            Text::NoTray => (
+                "Keine Zusammenstellung passt ins Budget.",
+                "No combination fits the budget.",
+            ),
+            Text::Closed => ("Geschlossen", "Closed"),
+            Text::Weekend => ("Wochenende", "weekend"),
+            Text::NewYear => ("Neujahr", "New Year's Day"),
+            Text::WomensDay => ("Internationaler Frauentag", "International Women's Day"),
+            Text::GoodFriday => ("Karfreitag", "Good Friday"),
+            Text::EasterMonday => ("Ostermontag", "Easter Monday"),
+            Text::LabourDay => ("Tag der Arbeit", "Labour Day"),
+            Text::LiberationDay => ("Tag der Befreiung", "Liberation Day"),
+            Text::AscensionDay => ("Christi Himmelfahrt", "Ascension Day"),
+            Text::WhitMonday => ("Pfingstmontag", "Whit Monday"),
+            Text::GermanUnity => ("Tag der Deutschen Einheit", "German Unity Day"),
+            Text::ChristmasDay => ("1. Weihnachtstag", "Christmas Day"),
+            Text::BoxingDay => ("2. Weihnachtstag", "Boxing Day"),
+            Text::TuiMensas => ("Mensen", "Dining facilities"),
+            Text::TuiSearch => ("Suche", "Search"),
+            Text::TuiMenu => ("Speiseplan", "Menu"),
+            Text::TuiNoMeals => ("Keine passenden Gerichte.", "No matching meals."),
+            Text::TuiDetails => ("Details", "Details"),
+            Text::TuiStudents => ("Studierende", "Students"),
+            Text::TuiEmployees => ("Beschäftigte", "Employees"),
+            Text::TuiGuests => ("Gäste", "Guests"),
+            Text::TuiAllergens => ("Allergene und Zusatzstoffe", "Allergens and additives"),
+            Text::TuiExcludeAllergens => (
+                "Allergene ausschließen (Leertaste)",
+                "Exclude allergens (space)",
+            ),
+            Text::TuiHelp => (
+                "←/→ Tag  ↑/↓ Auswahl  Tab Fokus  / Mensa suchen  Enter Details  g/y/r Ampel  v/e/o/k/f Kennzeichen  a Allergene  q Beenden",
+                "←/→ day  ↑/↓ select  Tab focus  / search mensa  Enter details  g/y/r color  v/e/o/k/f tags  a allergens  q quit",
+            ),
+            Text::WrongPrice => (
+                "Falscher Preis: {}. Bitte nutze Euro mit höchstens zwei Nachkommastellen, z.B. 2,35 oder 2.35.",
+                "Wrong price: {}. Please use euros with at most two decimal places, e.g. 2.35 or 2,35.",
+            ),
+            Text::WrongColor => (
+                "Falsche Farbe: {}. Bitte nutze green, yellow oder red.",
+                "Wrong color: {}. Please use green, yellow or red.",
+            ),
+            Text::WrongTag => (
+                "Falsches Tag: {}. Bitte nutze vegan, vegetarian, organic, climate friendly oder sustainable fishing.",
+                "Wrong tag: {}. Please use vegan, vegetarian, organic, climate friendly or sustainable fishing.",
+            ),
+            Text::WrongRating => (
+                "Falsche Bewertung: {}. Bitte nutze A, B, C, D oder E.",
+                "Wrong rating: {}. Please use A, B, C, D or E.",
+            ),
+            Text::WrongCourse => (
+                "Falscher Gang: {}. Bitte nutze starter, salad, soup, main, side, dessert oder other.",
+                "Wrong course: {}. Please use starter, salad, soup, main, side, dessert or other.",
+            ),
+            Text::WrongSortKey => (
+                "Falscher Sortierschlüssel: {}. Bitte nutze price, co2 oder water.",
+                "Wrong sort key: {}. Please use price, co2 or water.",
+            ),
+            Text::WrongCoordinates => (
+                "Falsche Koordinaten: {}. Bitte nutze BREITE,LÄNGE, z.B. 52.52,13.40.",
+                "Wrong coordinates: {}. Please use LATITUDE,LONGITUDE, e.g. 52.52,13.40.",
+            ),
+            Text::WrongLanguage => (
+                "Falsche Sprache: {}. Bitte nutze de oder en.",
+                "Wrong language: {}. Please use de or en.",
+            ),
+            Text::WrongDate => (
+                "Falsches Datum: {}. Bitte nutze z.B. 2024-05-17, 17.05., heute, morgen, freitag, nächsten freitag oder +2.",
+                "Wrong date: {}. Please use e.g. 2024-05-17, 17.05., today, tomorrow, friday, next friday or +2.",
+            ),
+            Text::WrongWeight => (
+                "Falsche Gewichtung: {}. Bitte nutze eine endliche Zahl, z.B. 1 oder 0.5.",
+                "Wrong weight: {}. Please use a finite number, e.g. 1 or 0.5.",
+            ),
+            Text::TooManyCourses => (
+                "Zu viele Gänge: {}. Ein Tablett hat höchstens {} Gänge.",
+                "Too many courses: {}. A tray has at most {} courses.",
+            ),
+            Text::WrongTime => (
+                "Falsche Uhrzeit: {}. Bitte nutze HH:MM, z.B. 15:00.",
+                "Wrong time: {}. Please use HH:MM, e.g. 15:00.",
+            ),
+            Text::NoFacilityMatches => (
+                "keine Mensa passt zu {}",
+                "no dining facility matches {}",
+            ),
+            Text::SeveralFacilitiesMatch => (
+                "{} passt zu mehreren Mensen:\n{}",
+                "{} matches several dining facilities:\n{}",
+            ),
+            Text::WrongLecturePeriod => (
+                "ungültige Vorlesungszeit {} (z.B. 2024-10-14..2025-02-15)",
+                "invalid lecture period {} (e.g. 2024-10-14..2025-02-15)",
+            ),
+            Text::NoConfigurationDirectory => ("kein Konfigurationsverzeichnis", "no configuration directory"),
+            Text::MissingParameter => ("fehlender Query-Parameter {}", "missing query parameter {}"),
+            Text::InvalidFilter => ("ungültiger Filter {}: {}", "invalid filter {}: {}"),
+            Text::UnterminatedString => (
+                "nicht abgeschlossene Zeichenkette ab Position {}",
+                "unterminated string starting at position {}",
+            ),
+            Text::UnexpectedCharacter => (
+                "unerwartetes Zeichen {} an Position {}",
+                "unexpected character {} at position {}",
+            ),
+            Text::ExpectedAtPosition => ("{} erwartet an Position {}", "expected {} at position {}"),
+            Text::ExpectedAtEnd => ("{} erwartet am Ende der Eingabe", "expected {} at end of input"),
+            Text::AtPosition => ("{} an Position {}", "{} at position {}"),
+            Text::ClosingParenthesis => ("')'", "')'"),
+            Text::NestedTooDeeply => (
+                "mehr als {} Ebenen aus Klammern und not an Position {}",
+                "more than {} levels of parentheses and not at position {}",
+            ),
+            Text::FilterTooLong => ("Filter mit mehr als {} Elementen", "filter with more than {} tokens"),
+            Text::PredicateOrComparison => (
+                "ein Prädikat, ein Vergleich oder '('",
+                "a predicate, a comparison or '('",
+            ),
+            Text::Value => ("ein Wert", "a value"),
+            Text::OperatorOrEnd => ("'and', 'or' oder Ende der Eingabe", "'and', 'or' or end of input"),
+            Text::ContainsOnlyName => (
+                "'~' gilt nur für name, main und side, nicht für {} an Position {}",
+                "'~' only applies to name, main and side, not {} at position {}",
+            ),
+            Text::InvalidPrice => ("ungültiger Preis {}", "invalid price {}"),
+            Text::UnknownField => (
+                "unbekanntes Feld {} an Position {}, bitte nutze price, price.student, price.employee, price.guest, co2, water, name, main oder side",
+                "unknown field {} at position {}, please use price, price.student, price.employee, price.guest, co2, water, name, main or side",
+            ),
+            Text::UnknownPredicate => ("unbekanntes Prädikat {}", "unknown predicate {}"),
+            Text::UnknownPredicateKind => (
+                "unbekanntes Prädikat {}, bitte nutze allergen:, tag: oder color:",
+                "unknown predicate {}, please use allergen:, tag: or color:",
+            ),
+            Text::HelpLang => (
+                "Wählt die Sprache der Ausgabe und der Speisepläne (de oder en); ohne sie ist die Ausgabe englisch und der Speiseplan deutsch",
+                "Chooses the language of the output and of the menus (de or en); without it, the output is in English and the menus in German",
+            ),
+            Text::HelpRollover => (
+                "Zeigt ab dieser Uhrzeit (Berlin) standardmäßig den Speiseplan des nächsten Werktags, z.B. 15:00",
+                "Shows the next serving day's menu by default after this time of day (Berlin), e.g. 15:00",
+            ),
+            Text::HelpQuiet => (
+                "Gibt nur Fehler aus, für Skripte, die nur den Exit-Code prüfen",
+                "Prints nothing but errors, for scripts that only check the exit code",
+            ),
+            Text::HelpCount => (
+                "Gibt nur die Anzahl der Ergebnisse aus, z.B. der passenden Gerichte",
+                "Prints only the number of results, e.g. of matching meals",
+            ),
+            Text::HelpMenuCommand => ("Zeigt den Speiseplan an", "Displays the menu"),
+            Text::HelpCodesCommand => (
+                "Listet alle verfügbaren Mensen auf",
+                "Lists all available dining facilities",
+            ),
+            Text::HelpCodesPattern => (
+                "Sucht nach einem bestimmten Muster",
+                "Searches for a specific pattern",
+            ),
+            Text::HelpCodesUniversity => (
+                "Listet nur Mensen einer bestimmten Hochschule auf",
+                "Lists only dining facilities serving a specific university",
+            ),
+            Text::HelpNear => (
+                "Listet die Mensen auf, die den Koordinaten am nächsten liegen",
+                "Lists the dining facilities closest to the given coordinates",
+            ),
+            Text::HelpNearLimit => (
+                "Begrenzt die Anzahl der mit --near aufgelisteten Mensen",
+                "Limits the number of facilities listed with --near",
+            ),
+            Text::HelpAllergensCommand => (
+                "Listet alle Allergen-Kürzel auf",
+                "List all allergen codes",
+            ),
+            Text::HelpFavoritesCommand => (
+                "Verwaltet Lieblingsgerichte",
+                "Manages favorite dishes",
+            ),
+            Text::HelpSearchCommand => (
+                "Durchsucht die Speisepläne aller Mensen nach einem Gericht",
+                "Searches the menus of all dining facilities for a dish",
+            ),
+            Text::HelpTrayCommand => (
+                "Stellt die besten Tabletts aus einem Gericht pro Gang innerhalb eines Budgets zusammen",
+                "Puts together the best trays of one meal per course within a budget",
+            ),
+            Text::HelpPlanCommand => (
+                "Plant ein Gericht pro Tag für die kommende Woche innerhalb eines Budgets",
+                "Plans one meal per day for the upcoming week within a budget",
+            ),
+            Text::HelpTuiCommand => (
+                "Durchstöbert die Speisepläne interaktiv in einer Vollbild-Terminaloberfläche",
+                "Browses the menus interactively in a full-screen terminal interface",
+            ),
+            Text::HelpColors => (
+                "Zeigt nur Gerichte mit den angegebenen Ampelfarben an",
+                "Displays only meals with the specified colors",
+            ),
+            Text::HelpTags => (
+                "Zeigt nur Gerichte mit mindestens einem der angegebenen Tags an",
+                "Displays only meals with any of the specified tags",
+            ),
+            Text::HelpRequireAllTags => (
+                "Zeigt nur Gerichte mit allen angegebenen Tags an",
+                "Displays only meals with all of the specified tags",
+            ),
+            Text::HelpExcludeTags => (
+                "Blendet Gerichte mit den angegebenen Tags aus",
+                "Displays no meals with the specified tags",
+            ),
+            Text::HelpMaxPrice => (
+                "Blendet Gerichte aus, die teurer als der angegebene Preis sind",
+                "Displays no meals more expensive than a given price",
+            ),
+            Text::HelpMaxCo2 => (
+                "Blendet Gerichte mit schlechterer CO2-Bewertung als der angegebenen aus (A bis E)",
+                "Displays no meals with a worse CO2 rating than the specified one (A to E)",
+            ),
+            Text::HelpMaxWater => (
+                "Blendet Gerichte mit schlechterer Wasser-Bewertung als der angegebenen aus (A bis E)",
+                "Displays no meals with a worse water rating than the specified one (A to E)",
+            ),
+            Text::HelpMaxKcal => (
+                "Blendet Gerichte mit mehr Kilokalorien als angegeben aus",
+                "Displays no meals with more kilocalories than the specified amount",
+            ),
+            Text::HelpMinProtein => (
+                "Blendet Gerichte mit weniger Eiweiß als angegeben (in Gramm) aus",
+                "Displays no meals with less protein than the specified amount in grams",
+            ),
+            Text::HelpNutrition => (
+                "Zeigt die Nährwerte der Gerichte an",
+                "Displays the nutrition facts of the meals",
+            ),
+            Text::HelpCourses => (
+                "Zeigt nur die angegebenen Gänge an (starter, salad, soup, main, side, dessert, other)",
+                "Displays only the specified courses (starter, salad, soup, main, side, dessert, other)",
+            ),
+            Text::HelpNoSides => ("Blendet Beilagen aus", "Displays no side dishes"),
+            Text::HelpSort => (
+                "Sortiert die Gerichte jeder Gruppe nach price, co2 oder water",
+                "Sorts the meals of each group by price, co2 or water",
+            ),
+            Text::HelpAllergens => (
+                "Blendet Gerichte mit den angegebenen Allergenen aus",
+                "Displays no meals containing the specified allergens",
+            ),
+            Text::HelpDate => (
+                "Wählt das Datum des Speiseplans, z.B. 2024-05-17, 17.05., heute, morgen, freitag, nächsten freitag oder +2",
+                "Chooses the menu date, e.g. 2024-05-17, 17.05., today, tomorrow, friday, next friday or +2",
+            ),
+            Text::HelpNextOpen => (
+                "Springt zum nächsten Tag mit Speiseplan, wenn die Mensa geschlossen hat",
+                "Skips forward to the next day with a menu if the dining facility is closed",
+            ),
+            Text::HelpBilingual => (
+                "Zeigt die Namen der Gerichte auf Deutsch und Englisch an",
+                "Displays the meal names in both German and English",
+            ),
+            Text::HelpMensa => (
+                "Wählt eine Mensa über Nummer, Slug oder Namen aus",
+                "Chooses a dining facility by code, slug or name",
+            ),
+            Text::HelpFilter => (
+                "Zeigt nur Gerichte an, die zu einem Filterausdruck passen, z.B. '(vegan or vegetarian) and price < 3.00 and not allergen:22a and co2 <= B and name ~ \"bowl\"'",
+                "Displays only meals matching a filter expression, e.g. '(vegan or vegetarian) and price < 3.00 and not allergen:22a and co2 <= B and name ~ \"bowl\"'",
+            ),
+            Text::HelpFavorites => (
+                "Hebt Gerichte hervor, die zu den angegebenen Mustern passen (/.../ für einen regulären Ausdruck)",
+                "Highlights meals matching the specified patterns (use /.../ for a regular expression)",
+            ),
+            Text::HelpQuery => (
+                "Durchsucht die Namen der Gerichte nach diesem Teilstring",
+                "Searches meal names for this substring",
+            ),
+            Text::HelpSearchUniversity => (
+                "Durchsucht nur die Mensen einer bestimmten Hochschule",
+                "Searches only the dining facilities of a specific university",
+            ),
+            Text::HelpPriceWeight => (
+                "Gewichtet den Preis eines Tabletts pro Euro",
+                "Weighs the price of a tray per euro",
+            ),
+            Text::HelpColorWeight => (
+                "Gewichtet die Ampelfarbe pro Stufe von grün bis rot",
+                "Weighs the traffic light color per step from green to red",
+            ),
+            Text::HelpCo2Weight => (
+                "Gewichtet die CO2-Bewertung pro Stufe von A bis E",
+                "Weighs the CO2 rating per step from A to E",
+            ),
+            Text::HelpTrayBudget => (
+                "Gibt höchstens so viel für ein Tablett aus (Studierendenpreise)",
+                "Spends at most this much on a tray (student prices)",
+            ),
+            Text::HelpTrayCourses => (
+                "Legt ein Gericht jedes dieser Gänge auf das Tablett",
+                "Puts one meal of each of these courses on the tray",
+            ),
+            Text::HelpTrayLimit => (
+                "Listet höchstens so viele Tabletts auf",
+                "Lists at most this many trays",
+            ),
+            Text::HelpPlanBudget => (
+                "Gibt in der ganzen Woche höchstens so viel aus (Studierendenpreise)",
+                "Spends at most this much in the whole week (student prices)",
+            ),
+            Text::HelpMaxRed => (
+                "Plant höchstens so viele rot markierte Gerichte ein",
+                "Plans at most this many meals marked red on the traffic light",
+            ),
+            Text::HelpAllowRepeats => (
+                "Erlaubt dieselbe Hauptkomponente an mehreren Tagen",
+                "Allows the same main component on several days",
+            ),
+            Text::HelpExtraMensas => (
+                "Berücksichtigt auch die Speisepläne dieser Mensen",
+                "Also considers the menus of these dining facilities",
+            ),
+            Text::HelpFavoriteAdd => (
+                "Merkt sich ein Lieblingsgericht",
+                "Registers a favorite dish",
+            ),
+            Text::HelpRegex => (
+                "Behandelt das Muster als regulären Ausdruck",
+                "Treats the pattern as a regular expression",
+            ),
+            Text::HelpFavoriteMensas => (
+                "Beschränkt das Lieblingsgericht auf die angegebenen Mensen",
+                "Restricts the favorite to the specified dining facilities",
+            ),
+            Text::HelpFavoriteRemove => (
+                "Vergisst ein Lieblingsgericht",
+                "Unregisters a favorite dish",
+            ),
+            Text::HelpFavoriteList => (
+                "Listet alle Lieblingsgerichte auf",
+                "Lists all favorite dishes",
+            ),
+            Text::HelpFavoriteCheck => (
+                "Sucht in der kommenden Woche nach Lieblingsgerichten",
+                "Scans the upcoming week for favorite dishes",
+            ),
+            Text::HelpCheckMensas => (
+                "Wählt die Mensen für Lieblingsgerichte ohne Beschränkung",
+                "Chooses the dining facilities for favorites without a restriction",
+            ),
+            Text::HelpHeadingUsage => ("AUFRUF:", "USAGE:"),
+            Text::HelpHeadingFlags => ("SCHALTER:", "FLAGS:"),
+            Text::HelpHeadingOptions => ("OPTIONEN:", "OPTIONS:"),
+            Text::HelpHeadingArgs => ("ARGUMENTE:", "ARGS:"),
+            Text::HelpHeadingSubcommands => ("UNTERBEFEHLE:", "SUBCOMMANDS:"),
+            Text::HelpHelp => ("Zeigt diese Hilfe an", "Prints help information"),
+            Text::HelpVersion => ("Zeigt die Version an", "Prints version information"),
+            Text::HelpHelpSubcommand => (
+                "Zeigt diese Hilfe oder die der angegebenen Unterbefehle an",
+                "Prints this message or the help of the given subcommand(s)",
+            ),
+            Text::HelpMoreInformation => (
+                "Mehr Informationen mit --help",
+                "For more information try --help",
+            ),
+            Text::HelpErrorPrefix => ("Fehler:", "error:"),
+            Text::HelpDefault => ("[Standard: ", "[default: "),
+            Text::HelpErrorInvalidValue => ("Ungültiger Wert für", "Invalid value for"),
+            Text::HelpErrorFoundArgument => ("Das Argument", "Found argument"),
+            Text::HelpErrorUnexpected => (
+                "war nicht erwartet oder ist hier nicht gültig",
+                "which wasn't expected, or isn't valid in this context",
+            ),
+            Text::HelpErrorMissingArguments => (
+                "Folgende erforderliche Argumente fehlen:",
+                "The following required arguments were not provided:",
+            ),
+            Text::HelpErrorMissingValue => (
+                "braucht einen Wert, aber es wurde keiner angegeben",
+                "requires a value but none was supplied",
+            ),
+            Text::HelpErrorConflict => (
+                "kann nicht zusammen genutzt werden mit",
+                "cannot be used with",
+            ),
+            Text::HelpErrorArgument => ("Das Argument", "The argument"),
+            Text::HelpErrorPossibleValue => (
+                "ist kein gültiger Wert für",
+                "isn't a valid value for",
+            ),
+            Text::HelpErrorPossibleValues => ("[mögliche Werte:", "[possible values:"),
+            Text::HelpErrorSubcommand => ("Der Unterbefehl", "The subcommand"),
+            Text::HelpErrorUnrecognized => ("wurde nicht erkannt", "wasn't recognized"),
+            Text::HelpErrorMissingSubcommand => (
+                "braucht einen Unterbefehl, aber es wurde keiner angegeben",
+                "requires a subcommand, but one was not provided",
+            ),
+            Text::HelpErrorRetry => (
+                "Falls diese Meldung ein Irrtum ist, versuche es mit",
+                "If you believe you received this message in error, try re-running with",
+            ),
+            Text::HelpErrorDidYouMean => ("Meintest du", "Did you mean"),
+        }
+    }
+}
+
+/// Looks up a text in the current language.
+pub fn text(text: Text) -> &'static str {
+    let (de, en) = text.translations();
+    match language() {
+        Language::De => de,
+        Language::En => en,
+    }
+}
+
+/// Looks up a text in the current language and fills its `{}` placeholders in order.
+pub fn format(text: Text, arguments: &[&dyn Display]) -> String {
+    let mut result = String::new();
+    let mut arguments = arguments.iter();
+    for (index, part) in self::text(text).split("{}").enumerate() {
+        if index > 0 {
+            if let Some(argument) = arguments.next() {
+                result.push_str(&argument.to_string());
+            }
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+pub fn weekday(weekday: Weekday) -> &'static str {
+    match (language(), weekday) {
+        (Language::De, Weekday::Mon) => "Mo",
+        (Language::De, Weekday::Tue) => "Di",
+        (Language::De, Weekday::Wed) => "Mi",
+        (Language::De, Weekday::Thu) => "Do",
+        (Language::De, Weekday::Fri) => "Fr",
+        (Language::De, Weekday::Sat) => "Sa",
+        (Language::De, Weekday::Sun) => "So",
+        (Language::En, Weekday::Mon) => "Mon",
+        (Language::En, Weekday::Tue) => "Tue",
+        (Language::En, Weekday::Wed) => "Wed",
+        (Language::En, Weekday::Thu) => "Thu",
+        (Language::En, Weekday::Fri) => "Fri",
+        (Language::En, Weekday::Sat) => "Sat",
+        (Language::En, Weekday::Sun) => "Sun",
+    }
+}
+
+/// Formats a decimal number with the decimal separator of the current language.
+pub fn decimal(number: f64, precision: usize) -> String {
+    let formatted = format!("{:.*}", precision, number);
+    match language() {
+        Language::De => formatted.replace('.', ","),
+        Language::En => formatted,
+    }
+}
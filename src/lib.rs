@@ -1,15 +1,32 @@
 pub mod allergens;
 pub mod codes;
+pub mod components;
+pub mod dates;
 mod error;
+pub mod favorites;
+pub mod filter;
+mod fuzzy;
+pub mod geo;
+pub mod holidays;
+pub mod hours;
+pub mod i18n;
 pub mod menu;
+pub mod nutrition;
+pub mod plan;
+pub mod search;
+pub mod tray;
+#[cfg(feature = "tui")]
+pub mod tui;
 mod utility;
 
+pub use error::Error;
+
 use ansi_term::Style;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct MensaCode(pub u16);
 
 impl From<u16> for MensaCode {
@@ -51,29 +68,71 @@ impl<Item> Response<Item> {
         }
         Response(groups)
     }
+
+    /// Counts the items in all groups.
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|group| group.items.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|group| group.items.is_empty())
+    }
+
+    /// Keeps only the groups matching the given predicate.
+    pub fn retain_groups(mut self, predicate: impl Fn(&Group<Item>) -> bool) -> Self {
+        self.0.retain(predicate);
+        self
+    }
+
+    /// Sorts the items of every group by the given key, keeping the order of the groups.
+    pub fn sort_by_key<Key: Ord>(mut self, key: impl Fn(&Item) -> Key) -> Self {
+        for group in &mut self.0 {
+            group.items.sort_by_key(&key);
+        }
+        self
+    }
+
+    pub fn map<Other>(self, function: impl Fn(Item) -> Other) -> Response<Other> {
+        Response(
+            self.0
+                .into_iter()
+                .map(|group| Group {
+                    name: group.name,
+                    course: group.course,
+                    items: group.items.into_iter().map(&function).collect(),
+                })
+                .collect(),
+        )
+    }
 }
 
 impl<Item: Display> Display for Response<Item> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        Ok(for group in &self.0 {
+        for group in &self.0 {
             write!(f, "{}", group)?;
-        })
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Group<Item> {
     name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    course: Option<menu::Course>,
     items: Vec<Item>,
 }
 
 impl<Item: Display> Display for Group<Item> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{}",
-            Style::new().bold().paint(&self.name.to_uppercase(),)
-        )?;
+        // upstream group names are German, so other languages use the course instead
+        let name = match (i18n::language(), self.course) {
+            (i18n::Language::De, _) | (_, None) | (_, Some(menu::Course::Other)) => {
+                self.name.clone()
+            }
+            (_, Some(course)) => course.to_string(),
+        };
+        writeln!(f, "{}", Style::new().bold().paint(name.to_uppercase()))?;
         for meal in &self.items {
             write!(f, "{}", meal)?;
         }
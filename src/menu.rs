@@ -1,22 +1,51 @@
-use super::{error::Error, utility, Group, MensaCode, Response};
+use super::{
+    codes,
+    components::Components,
+    dates,
+    error::Error,
+    favorites::Favorite,
+    filter::Filter,
+    holidays::{self, Holiday},
+    i18n::{self, Language, Text},
+    nutrition::Nutrition,
+    utility, Group, MensaCode, Response,
+};
 use ansi_term::{Colour, Style};
-use chrono::{format::ParseError, Local, NaiveDate};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
 use regex::Regex;
 use reqwest::{blocking::Client, header};
+#[cfg(feature = "server")]
 use rocket::request::{FromQuery, Query};
 use scraper::{html::Html, ElementRef, Selector};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+/// An amount of money. It serializes as an integer number of cents and deserializes from either
+/// that or a decimal euro string; see [`euro`] for serializing decimal euro strings.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default, Serialize)]
 pub struct Cents(u64);
 
 impl Cents {
-    fn from_euro(euro: f64) -> Self {
-        Cents((euro * 100f64) as u64)
+    /// Subtracts `other`, returning `None` instead of a negative amount.
+    pub fn checked_sub(self, other: Cents) -> Option<Cents> {
+        self.0.checked_sub(other.0).map(Cents)
+    }
+
+    /// Subtracts `other`, stopping at zero instead of a negative amount.
+    pub fn saturating_sub(self, other: Cents) -> Cents {
+        Cents(self.0.saturating_sub(other.0))
+    }
+
+    /// Formats the amount as a decimal euro string independent of the language, e.g. "2.35".
+    pub fn to_decimal(self) -> String {
+        format!("{}.{:02}", self.0 / 100, self.0 % 100)
     }
 }
 
@@ -26,32 +55,158 @@ impl From<u64> for Cents {
     }
 }
 
+impl From<Cents> for u64 {
+    fn from(cents: Cents) -> Self {
+        cents.0
+    }
+}
+
 impl Display for Cents {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         let Cents(total_cents) = self;
         let euros = total_cents / 100;
         let cents = total_cents % 100;
-        write!(f, "{},{:02} €", euros, cents)
+        match i18n::language() {
+            Language::De => write!(f, "{},{:02} €", euros, cents),
+            Language::En => write!(f, "€{}.{:02}", euros, cents),
+        }
     }
 }
 
 impl FromStr for Cents {
-    type Err = std::num::ParseFloatError;
+    type Err = String;
+    /// Parses a euro amount like "2,35", "2.35", "2,5", "2" or "2,35 €" exactly, without going
+    /// through floating point.
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        match string.parse() {
-            Ok(float) => Ok(Cents::from_euro(float)),
-            Err(err) => Err(err),
+        let error = || i18n::format(Text::WrongPrice, &[&string]);
+        let amount = string.trim().trim_matches('€').trim();
+        let (euros, cents) = match amount.find([',', '.']) {
+            Some(index) => (&amount[..index], &amount[index + 1..]),
+            None => (amount, ""),
+        };
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (euros.is_empty() && cents.is_empty())
+            || !is_digits(euros)
+            || !is_digits(cents)
+            || cents.len() > 2
+        {
+            return Err(error());
+        }
+        let euros = if euros.is_empty() {
+            0
+        } else {
+            euros.parse::<u64>().map_err(|_| error())?
+        };
+        let cents = match cents.len() {
+            0 => 0,
+            1 => cents.parse::<u64>().map_err(|_| error())? * 10,
+            _ => cents.parse::<u64>().map_err(|_| error())?,
+        };
+        euros
+            .checked_mul(100)
+            .and_then(|euros| euros.checked_add(cents))
+            .map(Cents)
+            .ok_or_else(error)
+    }
+}
+
+impl Add for Cents {
+    type Output = Cents;
+    fn add(self, other: Cents) -> Cents {
+        Cents(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Cents {
+    fn add_assign(&mut self, other: Cents) {
+        self.0 += other.0;
+    }
+}
+
+impl Sum for Cents {
+    fn sum<I: Iterator<Item = Cents>>(iter: I) -> Cents {
+        iter.fold(Cents(0), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Cents> for Cents {
+    fn sum<I: Iterator<Item = &'a Cents>>(iter: I) -> Cents {
+        iter.copied().sum()
+    }
+}
+
+impl<'de> Deserialize<'de> for Cents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CentsVisitor;
+
+        impl<'de> Visitor<'de> for CentsVisitor {
+            type Value = Cents;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "an integer number of cents or a decimal euro string")
+            }
+
+            fn visit_u64<E: de::Error>(self, cents: u64) -> Result<Cents, E> {
+                Ok(Cents(cents))
+            }
+
+            fn visit_i64<E: de::Error>(self, cents: i64) -> Result<Cents, E> {
+                u64::try_from(cents)
+                    .map(Cents)
+                    .map_err(|_| E::custom("negative amount of money"))
+            }
+
+            fn visit_str<E: de::Error>(self, string: &str) -> Result<Cents, E> {
+                string.parse().map_err(E::custom)
+            }
         }
+
+        deserializer.deserialize_any(CentsVisitor)
+    }
+}
+
+/// Serializes `Cents` as a decimal euro string like "2.35" instead of an integer number of
+/// cents, for use with `#[serde(with = "menstruation::menu::euro")]`.
+pub mod euro {
+    use super::Cents;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(cents: &Cents, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&cents.to_decimal())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Cents, D::Error> {
+        Cents::deserialize(deserializer)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Meal {
     pub name: String,
+    #[serde(default)]
+    pub components: Components,
     pub color: Color,
     pub tags: HashSet<Tag>,
+    pub co2_rating: Option<Rating>,
+    pub water_rating: Option<Rating>,
     pub price: Option<Price>,
     pub allergens: HashSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nutrition: Option<Nutrition>,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub names: Option<Names>,
+    /// Problems while reading the meal, e.g. icons that stw.berlin introduced recently
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// The name of a meal in both languages of stw.berlin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Names {
+    pub de: String,
+    pub en: String,
 }
 
 impl TryFrom<Html> for Response<Meal> {
@@ -67,6 +222,211 @@ impl TryFrom<Html> for Response<Meal> {
     }
 }
 
+impl Response<Meal> {
+    /// Lists the problems of all meals, e.g. icons that could not be recognized, which usually
+    /// means that stw.berlin introduced a new label.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = self
+            .0
+            .iter()
+            .flat_map(|group| &group.items)
+            .flat_map(|meal| meal.warnings.iter().cloned())
+            .collect::<Vec<_>>();
+        warnings.sort();
+        warnings.dedup();
+        warnings
+    }
+
+    /// Attaches the names from `translation`, the same menu in the other language, to the meals.
+    /// Groups and the meals within them are matched by position, so nothing is attached if the
+    /// number of groups differs, and groups whose lengths differ are left without translated names.
+    fn with_names(self, translation: Response<Meal>, language: Language) -> Self {
+        if translation.0.len() != self.0.len() {
+            return self;
+        }
+        let mut translated_groups = translation.0.into_iter();
+        Response(
+            self.0
+                .into_iter()
+                .map(|group| match translated_groups.next() {
+                    Some(translated) if translated.items.len() == group.items.len() => Group {
+                        items: group
+                            .items
+                            .into_iter()
+                            .zip(translated.items)
+                            .map(|(meal, translated)| {
+                                let (de, en) = match language {
+                                    Language::De => (meal.name.clone(), translated.name),
+                                    Language::En => (translated.name, meal.name.clone()),
+                                };
+                                Meal {
+                                    names: Some(Names { de, en }),
+                                    ..meal
+                                }
+                            })
+                            .collect(),
+                        ..group
+                    },
+                    _ => group,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Why a dining facility serves no meals on a day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum Closure {
+    Weekend,
+    Holiday(Holiday),
+    /// A notice on the menu page, e.g. about semester break closures
+    Notice(String),
+    Unknown,
+}
+
+impl Closure {
+    fn on(date: NaiveDate, notice: Option<String>) -> Self {
+        if let Some(holiday) = holidays::on(date) {
+            Closure::Holiday(holiday)
+        } else if date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun {
+            Closure::Weekend
+        } else if let Some(notice) = notice {
+            Closure::Notice(notice)
+        } else {
+            Closure::Unknown
+        }
+    }
+}
+
+impl Display for Closure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let closed = i18n::text(Text::Closed);
+        match self {
+            Closure::Weekend => write!(f, "{} ({})", closed, i18n::text(Text::Weekend)),
+            Closure::Holiday(holiday) => write!(f, "{} ({})", closed, holiday),
+            Closure::Notice(notice) => write!(f, "{}: {}", closed, notice),
+            Closure::Unknown => write!(f, "{}", closed),
+        }
+    }
+}
+
+/// The menu of a dining facility on a day. It is `closed` if the facility published no meals at
+/// all, before any filters are applied.
+#[derive(Debug, Serialize)]
+pub struct Menu {
+    pub date: NaiveDate,
+    pub closed: Option<Closure>,
+    /// Announcements outside the meal groups, e.g. theme weeks or payment outages
+    pub notices: Vec<String>,
+    pub groups: Response<Meal>,
+}
+
+impl Display for Menu {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let closure_notice = match &self.closed {
+            Some(Closure::Notice(notice)) => Some(notice),
+            _ => None,
+        };
+        for notice in &self.notices {
+            // the closure line below repeats it already
+            if Some(notice) != closure_notice {
+                writeln!(f, "{} {}", Colour::Yellow.bold().paint("!"), notice)?;
+            }
+        }
+        if let Some(closure) = &self.closed {
+            writeln!(f, "{}", Colour::Red.bold().paint(closure.to_string()))?;
+        }
+        write!(f, "{}", self.groups)
+    }
+}
+
+/// Elements whose text is never part of a notice, like scripts and navigation controls.
+const NON_NOTICE_ELEMENTS: &[&str] = &[
+    "script", "style", "a", "button", "form", "label", "select", "option", "nav",
+];
+
+/// Ids and classes of elements that are no notices, such as the icon legend or the date header
+/// above the menu. They are compared exactly, so that e.g. a class "update" is still a notice.
+const NON_NOTICE_NAMES: &[&str] = &["legend", "legende", "datum", "date"];
+
+fn is_non_notice(element: &ElementRef) -> bool {
+    let element = element.value();
+    NON_NOTICE_ELEMENTS.contains(&element.name())
+        || element
+            .id()
+            .into_iter()
+            .chain(element.classes())
+            .any(|name| NON_NOTICE_NAMES.contains(&&name.to_lowercase()[..]))
+}
+
+/// Elements that hold one notice each, with their text joined into a single line.
+const NOTICE_BLOCKS: &[&str] = &["p", "div", "li", "h1", "h2", "h3", "h4", "h5", "h6", "td"];
+
+/// Collects the text outside the meal groups, where stw.berlin announces theme weeks, reduced
+/// offers, closures or payment outages, as one line per paragraph.
+fn notices(html: &Html) -> Vec<String> {
+    static DATE_HEADER: OnceLock<Regex> = OnceLock::new();
+    let date_header = DATE_HEADER
+        .get_or_init(|| Regex::new(r"^(?:\w+,? )?\d{1,2}\.\d{1,2}\.(?:\d{2,4})?$").unwrap());
+    let group_selector = Selector::parse(".splGroupWrapper").unwrap();
+    let mut blocks = Vec::<(_, String)>::new();
+    for node in html.root_element().descendants() {
+        let text = match node.value().as_text() {
+            Some(text) if !text.trim().is_empty() => text,
+            _ => continue,
+        };
+        let mut ancestors = node.ancestors().filter_map(ElementRef::wrap);
+        if ancestors
+            .clone()
+            .any(|element| group_selector.matches(&element) || is_non_notice(&element))
+        {
+            continue;
+        }
+        let block = ancestors
+            .find(|element| NOTICE_BLOCKS.contains(&element.value().name()))
+            .map(|element| element.id());
+        match blocks.last_mut() {
+            // e.g. separated only by a <br>
+            Some((last, line)) if *last == block => {
+                line.push(' ');
+                *line += &text[..];
+            }
+            _ => blocks.push((block, text.to_string())),
+        }
+    }
+    let mut notices = Vec::<String>::new();
+    for (_, line) in blocks {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !notices.contains(&line) && !date_header.is_match(&line) {
+            notices.push(line);
+        }
+    }
+    notices
+}
+
+impl Menu {
+    fn parse(html: Html, date: NaiveDate) -> Result<Self, Error> {
+        static CLOSURE_NOTICE: OnceLock<Regex> = OnceLock::new();
+        let closure_notice = CLOSURE_NOTICE.get_or_init(|| {
+            Regex::new(r"(?i)geschlossen|schlie(?:ß|ss)|betriebsferien|closed").unwrap()
+        });
+        let notices = notices(&html);
+        let groups = Response::try_from(html)?;
+        Ok(Menu {
+            date,
+            closed: if groups.0.is_empty() {
+                let notice = notices.iter().find(|line| closure_notice.is_match(line));
+                Some(Closure::on(date, notice.cloned()))
+            } else {
+                None
+            },
+            notices,
+            groups,
+        })
+    }
+}
+
 impl TryFrom<ElementRef<'_>> for Group<Meal> {
     type Error = Error;
     fn try_from(html: ElementRef<'_>) -> Result<Self, Self::Error> {
@@ -83,7 +443,11 @@ impl TryFrom<ElementRef<'_>> for Group<Meal> {
             .map(Meal::try_from)
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| Error::Parse(format!("Group::items\n< {}", e)))?;
-        Ok(Group { name, items: meals })
+        Ok(Group {
+            course: Some(Course::from_group_name(&name)),
+            name,
+            items: meals,
+        })
     }
 }
 
@@ -94,25 +458,41 @@ impl Display for Meal {
                 Color::Green => Colour::Green,
                 Color::Red => Colour::Red,
                 Color::Yellow => Colour::Yellow,
+                Color::Unknown(_) => Colour::White,
             }
         }
+        let labels = self
+            .tags
+            .iter()
+            .map(|tag| format!("{}", tag))
+            .chain(self.co2_rating.iter().map(|rating| {
+                format!("{}", Style::new().italic().paint(format!("CO2 {}", rating)))
+            }))
+            .chain(self.water_rating.iter().map(|rating| {
+                format!("{}", Style::new().italic().paint(format!("H2O {}", rating)))
+            }))
+            .collect::<Vec<_>>();
+        let translation = match (&self.names, i18n::language()) {
+            (Some(names), Language::De) => format!(" {}", Style::new().dimmed().paint(&names.en)),
+            (Some(names), Language::En) => format!(" {}", Style::new().dimmed().paint(&names.de)),
+            (None, _) => String::new(),
+        };
         writeln!(
             f,
-            "[{}] {} {}",
-            format!(
-                "{}",
-                match &self.price {
-                    None => 0.into(),
-                    Some(p) => p.student,
-                }
-            ),
+            "[{}] {}{}{} {}",
+            match &self.price {
+                None => 0.into(),
+                Some(p) => p.student,
+            },
+            if self.favorite { "★ " } else { "" },
             to_ansi(&self.color).paint(&self.name),
-            self.tags
-                .iter()
-                .map(|tag| format!("{}", tag))
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+            translation,
+            labels.join(", ")
+        )?;
+        if let Some(nutrition) = &self.nutrition {
+            writeln!(f, "    {}", nutrition)?;
+        }
+        Ok(())
     }
 }
 
@@ -130,13 +510,6 @@ impl TryFrom<ElementRef<'_>> for Meal {
             .ok_or(Error::Parse("Meal icons".to_string()))?;
         let (color_htmls, tag_htmls) =
             utility::partition(|&src| src.contains("ampel"), &icons_html);
-        let color =
-            Color::from_img_src(color_htmls[0]).ok_or(Error::Parse("Meal::color".to_string()))?;
-        let tags = tag_htmls
-            .iter()
-            .map(|&src| Tag::from_img_src(src))
-            .collect::<Option<HashSet<_>>>()
-            .ok_or(Error::Parse("Meal::tags".to_string()))?;
         let meal_name = html
             .select(&meal_name_selector)
             .next()
@@ -144,34 +517,94 @@ impl TryFrom<ElementRef<'_>> for Meal {
             .inner_html()
             .trim()
             .to_string();
+        let mut warnings = Vec::new();
+
+        let color = match color_htmls.first() {
+            Some(src) => Color::from_img_src(src),
+            None => {
+                warnings.push(i18n::format(Text::NoColor, &[&meal_name]));
+                Color::Unknown(String::new())
+            }
+        };
+        if let Color::Unknown(src) = &color {
+            if !src.is_empty() {
+                warnings.push(i18n::format(Text::UnknownColorImage, &[src]));
+            }
+        }
+        let (rating_htmls, tag_htmls) =
+            utility::partition(|&&src| src.contains("_bewertung_"), &tag_htmls);
+        let co2_rating = rating_htmls
+            .iter()
+            .find_map(|&&src| Rating::from_img_src(src, "CO2"));
+        let water_rating = rating_htmls
+            .iter()
+            .find_map(|&&src| Rating::from_img_src(src, "H2O"));
+        for &&src in &rating_htmls {
+            if Rating::from_img_src(src, "CO2").is_none()
+                && Rating::from_img_src(src, "H2O").is_none()
+            {
+                warnings.push(i18n::format(Text::UnknownRatingImage, &[src]));
+            }
+        }
+        let tags = tag_htmls
+            .iter()
+            .map(|&&src| Tag::from_img_src(src))
+            .collect::<HashSet<_>>();
+        for tag in &tags {
+            if let Tag::Unknown(src) = tag {
+                warnings.push(i18n::format(Text::UnknownTagImage, &[src]));
+            }
+        }
         let price = Price::try_from(html).ok();
+        // the detail popup is part of the meal's markup, partly in title attributes
+        let nutrition = Nutrition::from_text(
+            &html
+                .text()
+                .chain(
+                    html.descendants()
+                        .filter_map(ElementRef::wrap)
+                        .filter_map(|element| element.value().attr("title")),
+                )
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
         let allergens = {
-            let parenthesized = Regex::new(r"\((.*)\)").unwrap();
-            let allergens_html = html
-                .select(&allergen_selector)
-                .next()
-                .expect("No allergens found")
-                .inner_html();
-            if let Some(captures) = parenthesized.captures(&allergens_html) {
-                String::from(&captures[1])
-                    .split(", ")
-                    .map(String::from)
-                    .collect()
-            } else {
-                HashSet::new()
+            static PARENTHESIZED: OnceLock<Regex> = OnceLock::new();
+            let parenthesized = PARENTHESIZED.get_or_init(|| Regex::new(r"\((.*)\)").unwrap());
+            match html.select(&allergen_selector).next() {
+                Some(allergens_html) => {
+                    match parenthesized.captures(&allergens_html.inner_html()) {
+                        Some(captures) => String::from(&captures[1])
+                            .split(", ")
+                            .map(String::from)
+                            .collect(),
+                        None => HashSet::new(),
+                    }
+                }
+                None => {
+                    warnings.push(i18n::format(Text::NoAllergens, &[&meal_name]));
+                    HashSet::new()
+                }
             }
         };
         Ok(Meal {
+            components: Components::parse(&meal_name),
             name: meal_name,
             tags,
+            co2_rating,
+            water_rating,
             color,
             price,
             allergens,
+            nutrition,
+            favorite: false,
+            names: None,
+            warnings,
         })
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Color {
     #[serde(rename = "green")]
     Green,
@@ -179,35 +612,34 @@ pub enum Color {
     Yellow,
     #[serde(rename = "red")]
     Red,
+    #[serde(rename = "unknown")]
+    Unknown(String),
 }
 
 impl FromStr for Color {
     type Err = String;
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         match string {
-            "green" => Ok(Color::Green),
-            "yellow" => Ok(Color::Yellow),
-            "red" => Ok(Color::Red),
-            _ => Err(format!(
-                "Falsche Farbe: {}. Bitte nutze green, yellow oder red.",
-                string
-            )),
+            "green" | "grün" | "gruen" => Ok(Color::Green),
+            "yellow" | "gelb" => Ok(Color::Yellow),
+            "red" | "rot" => Ok(Color::Red),
+            _ => Err(i18n::format(Text::WrongColor, &[&string])),
         }
     }
 }
 
 impl Color {
-    fn from_img_src(uri: &str) -> Option<Self> {
+    fn from_img_src(uri: &str) -> Self {
         match uri {
-            "/vendor/infomax/mensen/icons/ampel_gelb_70x65.png" => Some(Color::Yellow),
-            "/vendor/infomax/mensen/icons/ampel_gruen_70x65.png" => Some(Color::Green),
-            "/vendor/infomax/mensen/icons/ampel_rot_70x65.png" => Some(Color::Red),
-            _ => None,
+            "/vendor/infomax/mensen/icons/ampel_gelb_70x65.png" => Color::Yellow,
+            "/vendor/infomax/mensen/icons/ampel_gruen_70x65.png" => Color::Green,
+            "/vendor/infomax/mensen/icons/ampel_rot_70x65.png" => Color::Red,
+            _ => Color::Unknown(uri.to_string()),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Tag {
     #[serde(rename = "vegetarian")]
     Vegetarian,
@@ -219,26 +651,8 @@ pub enum Tag {
     SustainableFishing,
     #[serde(rename = "climate friendly")]
     ClimateFriendly,
-    #[serde(rename = "H2O A")]
-    H2oA,
-    #[serde(rename = "H2O B")]
-    H2oB,
-    #[serde(rename = "H2O C")]
-    H2oC,
-    #[serde(rename = "H2O D")]
-    H2oD,
-    #[serde(rename = "H2O E")]
-    H2oE,
-    #[serde(rename = "CO2 A")]
-    Co2A,
-    #[serde(rename = "CO2 B")]
-    Co2B,
-    #[serde(rename = "CO2 C")]
-    Co2C,
-    #[serde(rename = "CO2 D")]
-    Co2D,
-    #[serde(rename = "CO2 E")]
-    Co2E,
+    #[serde(rename = "unknown")]
+    Unknown(String),
 }
 
 impl FromStr for Tag {
@@ -246,14 +660,11 @@ impl FromStr for Tag {
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         match string {
             "vegan" => Ok(Tag::Vegan),
-            "vegetarian" => Ok(Tag::Vegetarian),
-            "organic" => Ok(Tag::Organic),
-            "climate friendly" => Ok(Tag::ClimateFriendly),
-            "sustainable fishing" => Ok(Tag::SustainableFishing),
-            _ => Err(format!(
-                "Wrong tag: {}. Please use vegan, vegetarian, organic, climate friendly or sustainable fishing.",
-                string
-            )),
+            "vegetarian" | "vegetarisch" => Ok(Tag::Vegetarian),
+            "organic" | "bio" => Ok(Tag::Organic),
+            "climate friendly" | "klimafreundlich" => Ok(Tag::ClimateFriendly),
+            "sustainable fishing" | "nachhaltige fischerei" => Ok(Tag::SustainableFishing),
+            _ => Err(i18n::format(Text::WrongTag, &[&string])),
         }
     }
 }
@@ -263,58 +674,188 @@ impl Display for Tag {
         write!(
             f,
             "{}",
-            Style::new().italic().paint(match self {
-                Tag::Vegetarian => "vegetarian",
-                Tag::Vegan => "vegan",
-                Tag::Organic => "organic",
-                Tag::SustainableFishing => "sustainable fishing",
-                Tag::ClimateFriendly => "climate friendly",
-                Tag::H2oA => "H2O A",
-                Tag::H2oB => "H2O B",
-                Tag::H2oC => "H2O C",
-                Tag::H2oD => "H2O D",
-                Tag::H2oE => "H2O E",
-                Tag::Co2A => "CO2 A",
-                Tag::Co2B => "CO2 B",
-                Tag::Co2C => "CO2 C",
-                Tag::Co2D => "CO2 D",
-                Tag::Co2E => "CO2 E",
-            })
+            Style::new().italic().paint(i18n::text(match self {
+                Tag::Vegetarian => Text::Vegetarian,
+                Tag::Vegan => Text::Vegan,
+                Tag::Organic => Text::Organic,
+                Tag::SustainableFishing => Text::SustainableFishing,
+                Tag::ClimateFriendly => Text::ClimateFriendly,
+                Tag::Unknown(_) => Text::UnknownTag,
+            }))
         )
     }
 }
 
 impl Tag {
-    fn from_img_src(uri: &str) -> Option<Tag> {
+    /// Returns the broader tag that this tag is a special case of, e.g. every vegan meal is also
+    /// vegetarian.
+    pub fn parent(&self) -> Option<Tag> {
+        match self {
+            Tag::Vegan => Some(Tag::Vegetarian),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a meal carrying `tags` has this tag, either directly or through one of its
+    /// more specific tags.
+    pub fn is_satisfied_by(&self, tags: &HashSet<Tag>) -> bool {
+        tags.iter().any(|tag| {
+            let mut current = Some(tag.clone());
+            while let Some(ancestor) = current {
+                if &ancestor == self {
+                    return true;
+                }
+                current = ancestor.parent();
+            }
+            false
+        })
+    }
+
+    fn from_img_src(uri: &str) -> Tag {
         match uri {
-            "/vendor/infomax/mensen/icons/1.png" => Some(Tag::Vegetarian),
-            "/vendor/infomax/mensen/icons/15.png" => Some(Tag::Vegan),
-            "/vendor/infomax/mensen/icons/18.png" => Some(Tag::Organic),
-            "/vendor/infomax/mensen/icons/38.png" => Some(Tag::SustainableFishing),
-            "/vendor/infomax/mensen/icons/43.png" => Some(Tag::ClimateFriendly),
-            "/vendor/infomax/mensen/icons/H2O_bewertung_A.svg?v=2" => Some(Tag::H2oA),
-            "/vendor/infomax/mensen/icons/H2O_bewertung_B.svg?v=2" => Some(Tag::H2oB),
-            "/vendor/infomax/mensen/icons/H2O_bewertung_C.svg?v=2" => Some(Tag::H2oC),
-            "/vendor/infomax/mensen/icons/H2O_bewertung_D.svg?v=2" => Some(Tag::H2oD),
-            "/vendor/infomax/mensen/icons/H2O_bewertung_E.svg?v=2" => Some(Tag::H2oE),
-            "/vendor/infomax/mensen/icons/CO2_bewertung_A.svg?v=2" => Some(Tag::Co2A),
-            "/vendor/infomax/mensen/icons/CO2_bewertung_B.svg?v=2" => Some(Tag::Co2B),
-            "/vendor/infomax/mensen/icons/CO2_bewertung_C.svg?v=2" => Some(Tag::Co2C),
-            "/vendor/infomax/mensen/icons/CO2_bewertung_D.svg?v=2" => Some(Tag::Co2D),
-            "/vendor/infomax/mensen/icons/CO2_bewertung_E.svg?v=2" => Some(Tag::Co2E),
-            _ => {
-                eprintln!("unknown tag for image: {}", uri);
-                None
+            "/vendor/infomax/mensen/icons/1.png" => Tag::Vegetarian,
+            "/vendor/infomax/mensen/icons/15.png" => Tag::Vegan,
+            "/vendor/infomax/mensen/icons/18.png" => Tag::Organic,
+            "/vendor/infomax/mensen/icons/38.png" => Tag::SustainableFishing,
+            "/vendor/infomax/mensen/icons/43.png" => Tag::ClimateFriendly,
+            _ => Tag::Unknown(uri.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum Rating {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl FromStr for Rating {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.to_uppercase().as_str() {
+            "A" => Ok(Rating::A),
+            "B" => Ok(Rating::B),
+            "C" => Ok(Rating::C),
+            "D" => Ok(Rating::D),
+            "E" => Ok(Rating::E),
+            _ => Err(i18n::format(Text::WrongRating, &[&string])),
+        }
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Rating {
+    /// Reads the rating from a sustainability icon such as `CO2_bewertung_B.svg`, where `kind` is
+    /// either `CO2` or `H2O`.
+    fn from_img_src(uri: &str, kind: &str) -> Option<Self> {
+        static RATING: OnceLock<Regex> = OnceLock::new();
+        let rating =
+            RATING.get_or_init(|| Regex::new(r"(CO2|H2O)_bewertung_([A-E])\.svg").unwrap());
+        rating
+            .captures(uri)
+            .filter(|captures| &captures[1] == kind)
+            .and_then(|captures| captures[2].parse().ok())
+    }
+}
+
+/// The course of a menu group, normalized from the upstream group name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Course {
+    #[serde(rename = "starter")]
+    Starter,
+    #[serde(rename = "salad")]
+    Salad,
+    #[serde(rename = "soup")]
+    Soup,
+    #[serde(rename = "main")]
+    Main,
+    #[serde(rename = "side")]
+    Side,
+    #[serde(rename = "dessert")]
+    Dessert,
+    #[serde(rename = "other")]
+    Other,
+}
+
+impl Course {
+    fn from_group_name(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "vorspeisen" | "starters" => Course::Starter,
+            "salate" | "salads" => Course::Salad,
+            "suppen" | "soups" => Course::Soup,
+            "essen" | "aktionen" | "hauptgerichte" | "dishes" | "main dishes" | "specials" => {
+                Course::Main
             }
+            "beilagen" | "side dishes" | "sides" => Course::Side,
+            "desserts" => Course::Dessert,
+            _ => Course::Other,
+        }
+    }
+}
+
+impl FromStr for Course {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "starter" => Ok(Course::Starter),
+            "salad" => Ok(Course::Salad),
+            "soup" => Ok(Course::Soup),
+            "main" => Ok(Course::Main),
+            "side" => Ok(Course::Side),
+            "dessert" => Ok(Course::Dessert),
+            "other" => Ok(Course::Other),
+            _ => Err(i18n::format(Text::WrongCourse, &[&string])),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+impl Display for Course {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let text = match self {
+            Course::Starter => Text::Starters,
+            Course::Salad => Text::Salads,
+            Course::Soup => Text::Soups,
+            Course::Main => Text::MainCourses,
+            Course::Side => Text::SideDishes,
+            Course::Dessert => Text::Desserts,
+            Course::Other => Text::OtherCourses,
+        };
+        write!(f, "{}", i18n::text(text))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Price,
+    Co2,
+    Water,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "price" => Ok(SortKey::Price),
+            "co2" => Ok(SortKey::Co2),
+            "water" => Ok(SortKey::Water),
+            _ => Err(i18n::format(Text::WrongSortKey, &[&string])),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Price {
     pub student: Cents,
-    employee: Cents,
-    guest: Cents,
+    pub employee: Cents,
+    pub guest: Cents,
 }
 
 impl TryFrom<ElementRef<'_>> for Price {
@@ -330,10 +871,8 @@ impl TryFrom<ElementRef<'_>> for Price {
             .nth(0)
             .ok_or(Error::Parse("Meal::price\n< not enough lines".to_string()))?
             .trim()
-            .replace("€", "")
-            .replace(",", ".")
             .split('/')
-            .map(|p| p.trim().parse::<f64>().map(Cents::from_euro))
+            .map(|p| p.parse::<Cents>())
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| Error::Parse(format!("Meal::price\n< {}", e)))?;
         Ok(Price {
@@ -344,111 +883,196 @@ impl TryFrom<ElementRef<'_>> for Price {
     }
 }
 
-pub fn get(options: MenuOptions) -> Result<Response<Meal>, Error> {
-    match Client::new()
-        .post("https://www.stw.berlin/xhr/speiseplan-wochentag.html")
+/// How many days `--next-open` looks ahead for an open dining facility, of which only serving
+/// days are fetched.
+const NEXT_OPEN_DAYS: i64 = 14;
+
+/// Fetches the unfiltered menu of a dining facility, in German or from the English version of
+/// stw.berlin.
+pub fn fetch(mensa: &MensaCode, date: NaiveDate, language: Language) -> Result<Menu, Error> {
+    let url = match language {
+        Language::De => "https://www.stw.berlin/xhr/speiseplan-wochentag.html",
+        Language::En => "https://www.stw.berlin/en/xhr/speiseplan-wochentag.html",
+    };
+    let text = Client::new()
+        .post(url)
         .form(&[
             ("week", "now"),
-            (
-                "date",
-                &options
-                    .date
-                    .unwrap_or_else(|| Local::now().date_naive())
-                    .format("%Y-%m-%d")
-                    .to_string(),
-            ),
-            ("resources_id", &options.mensa.0.to_string()),
+            ("date", &date.format("%Y-%m-%d").to_string()),
+            ("resources_id", &mensa.0.to_string()),
         ])
         .header(header::USER_AGENT, "Mozilla/5.0")
         .send()
-    {
-        Ok(response) => {
-            assert!(response.status().is_success());
-            Response::try_from(Html::parse_fragment(&response.text().unwrap()))
-                .map_err(|e| Error::Parse(format!("Response<Meal>\n< {}", e)))
-                .map(|response| response.filter(|meal| options.meal_matches(meal)))
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|e| Error::Net(e.to_string()))?;
+    Menu::parse(Html::parse_fragment(&text), date)
+        .map_err(|e| Error::Parse(format!("Menu\n< {}", e)))
+}
+
+pub fn get(options: MenuOptions, language: Language) -> Result<Menu, Error> {
+    let mut menu = fetch(
+        &options.mensa,
+        options.date.unwrap_or_else(dates::default_date),
+        language,
+    )?;
+    if options.next_open && menu.closed.is_some() {
+        // an error stops the search as well, so that it does not go on offline
+        let next_open = (1..=NEXT_OPEN_DAYS)
+            .map(|days| menu.date + Duration::days(days))
+            .filter(|date| dates::is_serving_day(*date))
+            .map(|date| fetch(&options.mensa, date, language))
+            .find(|next| next.as_ref().map_or(true, |next| next.closed.is_none()));
+        if let Some(next) = next_open {
+            menu = next?;
         }
-        Err(e) => Err(Error::Net(e.to_string())),
     }
+    let mut response = menu.groups;
+    if options.bilingual {
+        let other = match language {
+            Language::De => Language::En,
+            Language::En => Language::De,
+        };
+        response = response.with_names(fetch(&options.mensa, menu.date, other)?.groups, language);
+    }
+    let response = response
+        .retain_groups(|group| options.group_matches(group))
+        .filter(|meal| options.meal_matches(meal));
+    // `Ok` sorts before `Err`, so meals lacking the key end up last
+    let response = match options.sort {
+        Some(SortKey::Price) => {
+            response.sort_by_key(|meal| meal.price.as_ref().map(|price| price.student).ok_or(()))
+        }
+        Some(SortKey::Co2) => response.sort_by_key(|meal| meal.co2_rating.ok_or(())),
+        Some(SortKey::Water) => response.sort_by_key(|meal| meal.water_rating.ok_or(())),
+        None => response,
+    };
+    let groups = response.map(|meal| Meal {
+        favorite: options
+            .favorites
+            .iter()
+            .any(|favorite| favorite.matches(&options.mensa, &meal)),
+        nutrition: meal.nutrition.filter(|_| options.nutrition),
+        ..meal
+    });
+    Ok(Menu { groups, ..menu })
 }
 
-#[derive(Debug, structopt::StructOpt)]
+#[derive(Debug, Clone, structopt::StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 pub struct MenuOptions {
-    #[structopt(short, long, parse(try_from_str))]
-    /// Displays only meals with the specified colors
+    #[structopt(short, long, parse(try_from_str), help = i18n::text(Text::HelpColors))]
     pub colors: Vec<Color>,
-    #[structopt(short, long, parse(try_from_str))]
-    /// Displays only meals with the specified tags
+    #[structopt(short, long, parse(try_from_str), help = i18n::text(Text::HelpTags))]
     pub tags: Vec<Tag>,
-    #[structopt(short = "p", long)]
-    /// Displays no meals more expensive than a given price
+    #[structopt(long, help = i18n::text(Text::HelpRequireAllTags))]
+    pub require_all_tags: bool,
+    #[structopt(
+        short = "x",
+        long = "exclude-tag",
+        parse(try_from_str),
+        help = i18n::text(Text::HelpExcludeTags)
+    )]
+    pub exclude_tags: Vec<Tag>,
+    #[structopt(short = "p", long, help = i18n::text(Text::HelpMaxPrice))]
     pub max_price: Option<Cents>,
-    #[structopt(short, long)]
-    /// Displays no meals containing the specified allergens
+    #[structopt(long, parse(try_from_str), help = i18n::text(Text::HelpMaxCo2))]
+    pub max_co2: Option<Rating>,
+    #[structopt(long, parse(try_from_str), help = i18n::text(Text::HelpMaxWater))]
+    pub max_water: Option<Rating>,
+    #[structopt(long, help = i18n::text(Text::HelpMaxKcal))]
+    pub max_kcal: Option<f64>,
+    #[structopt(long, help = i18n::text(Text::HelpMinProtein))]
+    pub min_protein: Option<f64>,
+    #[structopt(long, help = i18n::text(Text::HelpNutrition))]
+    pub nutrition: bool,
+    #[structopt(long = "course", parse(try_from_str), help = i18n::text(Text::HelpCourses))]
+    pub courses: Vec<Course>,
+    #[structopt(long, help = i18n::text(Text::HelpNoSides))]
+    pub no_sides: bool,
+    #[structopt(short, long, parse(try_from_str), help = i18n::text(Text::HelpSort))]
+    pub sort: Option<SortKey>,
+    #[structopt(short, long, help = i18n::text(Text::HelpAllergens))]
     pub allergens: Vec<String>,
-    #[structopt(short, long, parse(try_from_str = parse_iso_date))]
-    /// Chooses the menu date
+    #[structopt(short, long, parse(try_from_str = dates::parse), help = i18n::text(Text::HelpDate))]
     pub date: Option<NaiveDate>,
-    #[structopt(short, long, default_value = "191")]
-    /// Chooses a dining facility
+    #[structopt(long, help = i18n::text(Text::HelpNextOpen))]
+    pub next_open: bool,
+    #[structopt(long, help = i18n::text(Text::HelpBilingual))]
+    pub bilingual: bool,
+    #[structopt(
+        short,
+        long,
+        default_value = "191",
+        parse(try_from_str = codes::parse),
+        help = i18n::text(Text::HelpMensa)
+    )]
     pub mensa: MensaCode,
+    #[structopt(long, parse(try_from_str), help = i18n::text(Text::HelpFilter))]
+    pub filter: Option<Filter>,
+    #[structopt(
+        short,
+        long = "favorite",
+        parse(try_from_str),
+        help = i18n::text(Text::HelpFavorites)
+    )]
+    pub favorites: Vec<Favorite>,
 }
 
-fn parse_iso_date(string: &str) -> Result<NaiveDate, ParseError> {
-    NaiveDate::parse_from_str(string, "%Y-%m-%d")
-}
-
+#[cfg(feature = "server")]
 impl<'a> FromQuery<'a> for MenuOptions {
-    type Error = ();
+    type Error = String;
 
     fn from_query(query: Query<'a>) -> Result<Self, Self::Error> {
-        fn query_values<T: FromStr>(key: &str, query: &Query) -> Vec<T> {
-            query
-                .clone()
-                .filter_map(|item| {
-                    if item.key == key {
-                        item.value.parse().ok()
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        }
-
-        fn query_value<T, E>(
-            key: &str,
-            query: &Query,
-            parse: impl Fn(&str) -> Result<T, E>,
-        ) -> Option<T> {
-            query
-                .clone()
-                .find(|item| item.key == key)
-                .map(|item| parse(item.value))
-                .and_then(|x| x.ok())
-        }
-
-        if let Some(mensa) = query_value("mensa", &query, |value| {
-            value.parse().map(|code: u16| code.into())
-        }) {
-            Ok(MenuOptions {
-                colors: query_values("color", &query),
-                tags: query_values("tag", &query),
-                max_price: query_value("max_price", &query, |value| {
-                    value.parse().map(|code: u64| code.into())
-                }),
-                allergens: query_values("allergen", &query),
-                date: query_value("date", &query, parse_iso_date),
-                mensa,
+        let mensa = utility::query_string("mensa", &query)
+            .ok_or_else(|| i18n::format(Text::MissingParameter, &[&"mensa"]))
+            .and_then(|value| codes::parse(&value))?;
+        let filter = utility::query_string("filter", &query)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| i18n::format(Text::InvalidFilter, &[&format!("{:?}", value), &e]))
             })
-        } else {
-            Err(())
-        }
+            .transpose()?;
+        let date = utility::query_string("date", &query)
+            .map(|value| dates::parse(&value))
+            .transpose()?;
+        Ok(MenuOptions {
+            colors: utility::query_values("color", &query),
+            tags: utility::query_parsed("tag", &query)?,
+            require_all_tags: utility::query_string("tag_mode", &query).as_deref() == Some("all"),
+            exclude_tags: utility::query_parsed("exclude_tag", &query)?,
+            max_price: utility::query_value("max_price", &query, |value| {
+                value.parse().map(|code: u64| code.into())
+            }),
+            max_co2: utility::query_value("max_co2", &query, str::parse),
+            max_water: utility::query_value("max_water", &query, str::parse),
+            max_kcal: utility::query_value("max_kcal", &query, str::parse),
+            min_protein: utility::query_value("min_protein", &query, str::parse),
+            nutrition: utility::query_value("nutrition", &query, str::parse).unwrap_or(true),
+            courses: utility::query_values("course", &query),
+            no_sides: utility::query_value("no_sides", &query, str::parse).unwrap_or(false),
+            sort: utility::query_value("sort", &query, str::parse),
+            allergens: utility::query_values("allergen", &query),
+            date,
+            next_open: utility::query_value("next_open", &query, str::parse).unwrap_or(false),
+            bilingual: utility::query_value("bilingual", &query, str::parse).unwrap_or(false),
+            mensa,
+            filter,
+            favorites: utility::query_parsed("favorite", &query)?,
+        })
     }
 }
 
 impl MenuOptions {
-    fn meal_matches(&self, meal: &Meal) -> bool {
+    pub(crate) fn group_matches(&self, group: &Group<Meal>) -> bool {
+        let course = group.course.unwrap_or(Course::Other);
+        let courses_ok = self.courses.is_empty() || self.courses.contains(&course);
+        let sides_ok = !self.no_sides || course != Course::Side;
+        courses_ok && sides_ok
+    }
+
+    pub(crate) fn meal_matches(&self, meal: &Meal) -> bool {
         let price_ok = if let Some(max) = self.max_price {
             if let Some(price) = &meal.price {
                 price.student <= max
@@ -458,16 +1082,214 @@ impl MenuOptions {
         } else {
             true
         };
+        let rating_ok = |max: Option<Rating>, rating: Option<Rating>| match (max, rating) {
+            (Some(max), Some(rating)) => rating <= max,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let co2_ok = rating_ok(self.max_co2, meal.co2_rating);
+        let water_ok = rating_ok(self.max_water, meal.water_rating);
+        let nutrition = meal.nutrition.unwrap_or_default();
+        let kcal_ok = match (self.max_kcal, nutrition.kilocalories) {
+            (Some(max), Some(kcal)) => kcal <= max,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let protein_ok = match (self.min_protein, nutrition.protein) {
+            (Some(min), Some(protein)) => protein >= min,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
         let colors_ok = self.colors.is_empty() || self.colors.contains(&meal.color);
-        let tags_ok = self.tags.is_empty()
-            || meal.tags.iter().any(|tag| {
-                self.tags.contains(tag)
-                    || (tag == &Tag::Vegan && self.tags.contains(&Tag::Vegetarian))
-            });
+        let tags_ok = if self.require_all_tags {
+            self.tags.iter().all(|tag| tag.is_satisfied_by(&meal.tags))
+        } else {
+            self.tags.is_empty() || self.tags.iter().any(|tag| tag.is_satisfied_by(&meal.tags))
+        };
+        let exclude_tags_ok = !self
+            .exclude_tags
+            .iter()
+            .any(|tag| tag.is_satisfied_by(&meal.tags));
         let allergens_ok = meal
             .allergens
             .iter()
             .all(|allergen| !self.allergens.contains(allergen));
-        price_ok && colors_ok && tags_ok && allergens_ok
+        let filter_ok = self
+            .filter
+            .as_ref()
+            .map_or(true, |filter| filter.matches(meal));
+        price_ok
+            && co2_ok
+            && water_ok
+            && kcal_ok
+            && protein_ok
+            && colors_ok
+            && tags_ok
+            && exclude_tags_ok
+            && allergens_ok
+            && filter_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meal(inner: &str) -> Meal {
+        let html = Html::parse_fragment(&format!(r#"<div class="splMeal">{}</div>"#, inner));
+        let selector = Selector::parse(".splMeal").unwrap();
+        Meal::try_from(html.select(&selector).next().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_prices_exactly() {
+        let cents = |string: &str| string.parse::<Cents>().map(u64::from);
+        assert_eq!(cents("2,35"), Ok(235));
+        assert_eq!(cents("2.35"), Ok(235));
+        assert_eq!(cents("2"), Ok(200));
+        assert_eq!(cents("2,5"), Ok(250));
+        assert_eq!(cents(",5"), Ok(50));
+        assert_eq!(cents(" 2,35 € "), Ok(235));
+        assert!(cents("-1").is_err());
+        assert!(cents("2,345").is_err());
+        assert!(cents("").is_err());
+        assert!(cents("2,3,4").is_err());
+        assert!(cents("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn serializes_cents_or_euro_strings() {
+        #[derive(Serialize, Deserialize)]
+        struct Prices {
+            cents: Cents,
+            #[serde(with = "euro")]
+            euros: Cents,
+        }
+        let prices = Prices {
+            cents: Cents::from(235),
+            euros: Cents::from(1205),
+        };
+        let json = serde_json::to_string(&prices).unwrap();
+        assert_eq!(json, r#"{"cents":235,"euros":"12.05"}"#);
+        let parsed = serde_json::from_str::<Prices>(&json).unwrap();
+        assert_eq!((parsed.cents, parsed.euros), (prices.cents, prices.euros));
+        // either form reads back through the same Deserialize
+        let swapped = serde_json::from_str::<Prices>(r#"{"cents":"2.35","euros":1205}"#).unwrap();
+        assert_eq!((swapped.cents, swapped.euros), (prices.cents, prices.euros));
+    }
+
+    #[test]
+    fn subtracts_without_negative_amounts() {
+        let (small, large) = (Cents::from(100), Cents::from(250));
+        assert_eq!(large.checked_sub(small), Some(Cents::from(150)));
+        assert_eq!(small.checked_sub(large), None);
+        assert_eq!(small.saturating_sub(large), Cents::default());
+    }
+
+    #[test]
+    fn reads_known_icons() {
+        let meal = meal(
+            r#"<img class="splIcon" src="/vendor/infomax/mensen/icons/ampel_gruen_70x65.png">
+            <img class="splIcon" src="/vendor/infomax/mensen/icons/15.png">
+            <img class="splIcon" src="/vendor/infomax/mensen/icons/CO2_bewertung_B.svg">
+            <span class="bold">Gemüsecurry</span>
+            <div class="toolt">Allergene (22a, 23)</div>"#,
+        );
+        assert_eq!(meal.color, Color::Green);
+        assert!(meal.tags.contains(&Tag::Vegan));
+        assert_eq!(meal.co2_rating, Some(Rating::B));
+        assert_eq!(meal.allergens.len(), 2);
+        assert!(meal.warnings.is_empty());
+    }
+
+    #[test]
+    fn warns_instead_of_failing() {
+        let meal = meal(
+            r#"<img class="splIcon" src="/vendor/infomax/mensen/icons/99.png">
+            <img class="splIcon" src="/vendor/infomax/mensen/icons/CO2_bewertung_F.svg">
+            <span class="bold">Gemüsecurry</span>"#,
+        );
+        assert_eq!(meal.color, Color::Unknown(String::new()));
+        assert!(meal.allergens.is_empty());
+        assert_eq!(meal.co2_rating, None);
+        assert_eq!(meal.warnings.len(), 4);
+    }
+
+    fn response(groups: &[&[&str]]) -> Response<Meal> {
+        Response(
+            groups
+                .iter()
+                .map(|names| Group {
+                    name: "Essen".to_string(),
+                    course: Some(Course::Main),
+                    items: names
+                        .iter()
+                        .map(|name| {
+                            meal(&format!(
+                                r#"<img class="splIcon" src="/vendor/infomax/mensen/icons/ampel_gruen_70x65.png">
+                                <span class="bold">{}</span><div class="toolt"></div>"#,
+                                name
+                            ))
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn attaches_names_only_to_matching_groups() {
+        let german = response(&[&["Eintopf", "Nudeln"], &["Pudding"]]);
+        let english = response(&[&["Stew", "Pasta"], &["Pudding", "Fruit"]]);
+        let names = german
+            .with_names(english, Language::De)
+            .0
+            .into_iter()
+            .map(|group| {
+                group
+                    .items
+                    .into_iter()
+                    .map(|meal| meal.names.map(|names| names.en))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                vec![Some("Stew".to_string()), Some("Pasta".to_string())],
+                vec![None]
+            ]
+        );
+    }
+
+    #[test]
+    fn attaches_no_names_if_the_groups_differ() {
+        let german = response(&[&["Eintopf"], &["Pudding"]]);
+        let english = response(&[&["Stew"]]);
+        let bilingual = german.with_names(english, Language::De);
+        assert!(bilingual
+            .0
+            .iter()
+            .flat_map(|group| &group.items)
+            .all(|meal| meal.names.is_none()));
+    }
+
+    #[test]
+    fn joins_notice_lines_and_skips_legend_and_date() {
+        let html = Html::parse_document(
+            r#"<div class="row"><div class="col-xs-12 datum">Freitag, 17.05.2024</div></div>
+            <p>Heute<br>geschlossen</p>
+            <p>Montag, 20.05.</p>
+            <div id="legende"><p>Lebensmittelampel: grün, gelb, rot</p></div>
+            <div class="update"><p>Kartenzahlung wieder möglich</p></div>
+            <div class="splGroupWrapper"><div class="splGroup">Essen</div></div>"#,
+        );
+        assert_eq!(
+            notices(&html),
+            vec![
+                "Heute geschlossen".to_string(),
+                "Kartenzahlung wieder möglich".to_string()
+            ]
+        );
     }
 }
@@ -0,0 +1,111 @@
+use super::i18n::{self, Text};
+use ansi_term::Style;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// The nutrition facts of a meal per portion, with masses in grams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nutrition {
+    pub kilojoules: Option<f64>,
+    pub kilocalories: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbohydrates: Option<f64>,
+    pub protein: Option<f64>,
+    pub salt: Option<f64>,
+}
+
+fn number(string: &str) -> Option<f64> {
+    string.replace(',', ".").parse().ok()
+}
+
+/// The patterns for kilojoules, kilocalories, fat, carbohydrates, protein and salt, compiled once.
+fn patterns() -> &'static [Regex; 6] {
+    static PATTERNS: OnceLock<[Regex; 6]> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let value = |unit: &str| format!(r"\s*[=:]?\s*(\d+(?:[.,]\d+)?)\s*{}", unit);
+        [
+            r"(\d+(?:[.,]\d+)?)\s*kJ".to_string(),
+            r"(\d+(?:[.,]\d+)?)\s*kcal".to_string(),
+            format!(r"(?i)\b(?:Fett|Fat){}", value("g")),
+            format!(r"(?i)\b(?:Kohlenhydrate|Carbohydrates?){}", value("g")),
+            format!(r"(?i)\b(?:Eiwei(?:ß|ss)|Protein){}", value("g")),
+            format!(r"(?i)\b(?:Salz|Salt){}", value("g")),
+        ]
+        .map(|pattern| Regex::new(&pattern).unwrap())
+    })
+}
+
+impl Nutrition {
+    /// Reads the nutrition facts from the text of a meal's detail popup, which lists lines like
+    /// "Brennwert = 2418 kJ / 578 kcal", "Fett = 27,8g" or "Eiweiß = 15,3g". Returns `None` if
+    /// none of them are present.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let [kilojoules, kilocalories, fat, carbohydrates, protein, salt] = patterns();
+        let find = |pattern: &Regex| {
+            pattern
+                .captures(text)
+                .and_then(|captures| number(&captures[1]))
+        };
+        let nutrition = Nutrition {
+            kilojoules: find(kilojoules),
+            kilocalories: find(kilocalories),
+            fat: find(fat),
+            carbohydrates: find(carbohydrates),
+            protein: find(protein),
+            salt: find(salt),
+        };
+        if nutrition == Nutrition::default() {
+            None
+        } else {
+            Some(nutrition)
+        }
+    }
+}
+
+impl Display for Nutrition {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let grams = |text: Text, amount: Option<f64>| {
+            amount.map(|amount| format!("{} {} g", i18n::text(text), i18n::decimal(amount, 1)))
+        };
+        let facts = vec![
+            self.kilocalories
+                .map(|kcal| format!("{} kcal", i18n::decimal(kcal, 0))),
+            grams(Text::Fat, self.fat),
+            grams(Text::Carbohydrates, self.carbohydrates),
+            grams(Text::Protein, self.protein),
+            grams(Text::Salt, self.salt),
+        ];
+        write!(
+            f,
+            "{}",
+            Style::new()
+                .dimmed()
+                .paint(facts.into_iter().flatten().collect::<Vec<_>>().join(" · "))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_detail_popup() {
+        let text = "Brennwert = 2418 kJ / 578 kcal Fett = 27,8g Kohlenhydrate = 60,1g \
+                    Eiweiß = 15,3g Salz = 2,1g";
+        assert_eq!(
+            Nutrition::from_text(text),
+            Some(Nutrition {
+                kilojoules: Some(2418.0),
+                kilocalories: Some(578.0),
+                fat: Some(27.8),
+                carbohydrates: Some(60.1),
+                protein: Some(15.3),
+                salt: Some(2.1),
+            })
+        );
+        assert_eq!(Nutrition::from_text("Gemüsecurry mit Reis"), None);
+    }
+}
@@ -0,0 +1,469 @@
+#[cfg(feature = "server")]
+use super::utility;
+use super::{
+    codes, dates,
+    error::Error,
+    favorites, fuzzy,
+    i18n::{self, Language, Text},
+    menu::{self, Cents, Color, Course, Meal, MenuOptions},
+    MensaCode,
+};
+use ansi_term::{Color as Colour, Style};
+use chrono::{Datelike, NaiveDate};
+#[cfg(feature = "server")]
+use rocket::request::{FromQuery, Query};
+use serde_derive::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// The number of cheapest meals per day that the planner considers.
+const CANDIDATES_PER_DAY: usize = 20;
+
+#[derive(Debug, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct PlanOptions {
+    #[structopt(long, help = i18n::text(Text::HelpPlanBudget))]
+    pub budget: Cents,
+    #[structopt(long, help = i18n::text(Text::HelpMaxRed))]
+    pub max_red: Option<usize>,
+    #[structopt(long, help = i18n::text(Text::HelpAllowRepeats))]
+    pub allow_repeats: bool,
+    #[structopt(
+        long = "extra-mensa",
+        parse(try_from_str = codes::parse),
+        help = i18n::text(Text::HelpExtraMensas)
+    )]
+    pub extra_mensas: Vec<MensaCode>,
+    #[structopt(flatten)]
+    pub menu: MenuOptions,
+}
+
+#[cfg(feature = "server")]
+impl<'a> FromQuery<'a> for PlanOptions {
+    type Error = String;
+
+    fn from_query(query: Query<'a>) -> Result<Self, Self::Error> {
+        let budget = utility::query_string("budget", &query)
+            .ok_or_else(|| i18n::format(Text::MissingParameter, &[&"budget"]))?
+            .parse()?;
+        let extra_mensas = utility::query_strings("extra_mensa", &query)
+            .iter()
+            .map(|value| codes::parse(value))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PlanOptions {
+            budget,
+            max_red: utility::query_value("max_red", &query, str::parse),
+            allow_repeats: utility::query_value("allow_repeats", &query, str::parse)
+                .unwrap_or(false),
+            extra_mensas,
+            menu: MenuOptions::from_query(query)?,
+        })
+    }
+}
+
+/// Why the planner left a day without a meal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Reason {
+    /// None of the dining facilities published a menu, e.g. because they are closed.
+    #[serde(rename = "no menu")]
+    NoMenu,
+    /// No meal passed the filters.
+    #[serde(rename = "no matching meal")]
+    NoMatchingMeal,
+    /// Every matching meal would have exceeded the remaining budget.
+    #[serde(rename = "budget")]
+    Budget,
+    /// The meals within budget would have repeated a main component or exceeded the red meals.
+    #[serde(rename = "variety")]
+    Variety,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            i18n::text(match self {
+                Reason::NoMenu => Text::PlanNoMenu,
+                Reason::NoMatchingMeal => Text::PlanNoMatchingMeal,
+                Reason::Budget => Text::PlanBudget,
+                Reason::Variety => Text::PlanVariety,
+            })
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub mensa: Option<MensaCode>,
+    pub meal: Option<Meal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty_because: Option<Reason>,
+}
+
+#[derive(Serialize)]
+pub struct Plan {
+    pub total: Cents,
+    pub budget: Cents,
+    pub days: Vec<Day>,
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        for day in &self.days {
+            write!(
+                f,
+                "{} ",
+                Style::new().bold().paint(format!(
+                    "{} {}",
+                    i18n::weekday(day.date.weekday()),
+                    day.date.format("%Y-%m-%d")
+                ))
+            )?;
+            match (&day.mensa, &day.meal, &day.empty_because) {
+                (Some(mensa), Some(meal), _) => write!(
+                    f,
+                    "{} {}",
+                    Colour::Green.paint(format!("{:>4}", mensa.to_string())),
+                    meal
+                )?,
+                (_, _, reason) => writeln!(
+                    f,
+                    "{}",
+                    Style::new().dimmed().paint(
+                        reason
+                            .map(|reason| format!("– {}", reason))
+                            .unwrap_or_default()
+                    )
+                )?,
+            }
+        }
+        writeln!(
+            f,
+            "{}",
+            Style::new()
+                .bold()
+                .paint(i18n::format(Text::PlanTotal, &[&self.total, &self.budget]))
+        )
+    }
+}
+
+struct Candidate {
+    mensa: MensaCode,
+    meal: Meal,
+    price: Cents,
+    main: String,
+}
+
+/// A branch-and-bound search for the plan that fills the most days and, among those, is the
+/// cheapest.
+struct Planner<'a> {
+    days: &'a [Vec<Candidate>],
+    budget: Cents,
+    max_red: Option<usize>,
+    allow_repeats: bool,
+    /// The cheapest way to fill every remaining day, indexed by the first remaining day
+    cheapest_rest: Vec<Cents>,
+    /// The number of remaining days with any candidates, indexed likewise
+    fillable_rest: Vec<usize>,
+    best: Option<(usize, Cents, Vec<Option<usize>>)>,
+}
+
+impl Planner<'_> {
+    fn search(
+        &mut self,
+        chosen: &mut Vec<Option<usize>>,
+        filled: usize,
+        spent: Cents,
+        reds: usize,
+    ) {
+        let day = chosen.len();
+        if let Some((best_filled, best_spent, _)) = &self.best {
+            let reachable = filled + self.fillable_rest[day];
+            if reachable < *best_filled
+                || (reachable == *best_filled && spent + self.cheapest_rest[day] >= *best_spent)
+            {
+                return;
+            }
+        }
+        if day == self.days.len() {
+            self.best = Some((filled, spent, chosen.clone()));
+            return;
+        }
+        for (index, candidate) in self.days[day].iter().enumerate() {
+            // candidates are sorted by price, so all further ones are too expensive as well
+            if spent + candidate.price > self.budget {
+                break;
+            }
+            let is_red = candidate.meal.color == Color::Red;
+            if is_red && self.max_red.is_some_and(|max| reds >= max) {
+                continue;
+            }
+            if !self.allow_repeats && self.repeats(chosen, &candidate.main) {
+                continue;
+            }
+            chosen.push(Some(index));
+            self.search(
+                chosen,
+                filled + 1,
+                spent + candidate.price,
+                reds + usize::from(is_red),
+            );
+            chosen.pop();
+        }
+        chosen.push(None);
+        self.search(chosen, filled, spent, reds);
+        chosen.pop();
+    }
+
+    fn repeats(&self, chosen: &[Option<usize>], main: &str) -> bool {
+        chosen
+            .iter()
+            .enumerate()
+            .filter_map(|(day, index)| index.map(|index| &self.days[day][index]))
+            .any(|candidate| candidate.main == main)
+    }
+}
+
+/// Picks at most one candidate per day, returning the total price and the index of the candidate
+/// chosen for each day.
+fn choose(
+    days: &[Vec<Candidate>],
+    budget: Cents,
+    max_red: Option<usize>,
+    allow_repeats: bool,
+) -> (Cents, Vec<Option<usize>>) {
+    let mut cheapest_rest = vec![Cents::default(); days.len() + 1];
+    let mut fillable_rest = vec![0; days.len() + 1];
+    for (day, candidates) in days.iter().enumerate().rev() {
+        let cheapest = candidates.first().map(|candidate| candidate.price);
+        cheapest_rest[day] = cheapest_rest[day + 1] + cheapest.unwrap_or_default();
+        fillable_rest[day] = fillable_rest[day + 1] + usize::from(cheapest.is_some());
+    }
+    let mut planner = Planner {
+        days,
+        budget,
+        max_red,
+        allow_repeats,
+        cheapest_rest,
+        fillable_rest,
+        best: None,
+    };
+    planner.search(&mut Vec::new(), 0, Cents::default(), 0);
+    planner
+        .best
+        .map(|(_, total, chosen)| (total, chosen))
+        .unwrap_or((Cents::default(), vec![None; days.len()]))
+}
+
+/// Fetches the menus of the upcoming serving days and picks one meal per day, filling as many
+/// days as possible within the weekly budget and the variety rules, as cheaply as possible.
+pub fn get(options: PlanOptions, language: Language) -> Result<Plan, Error> {
+    let PlanOptions {
+        budget,
+        max_red,
+        allow_repeats,
+        extra_mensas,
+        menu: mut menu_options,
+    } = options;
+    if menu_options.courses.is_empty() {
+        menu_options.courses = vec![Course::Main];
+    }
+    let start = menu_options.date.unwrap_or_else(dates::default_date);
+    let mut mensas = vec![menu_options.mensa.clone()];
+    mensas.extend(
+        extra_mensas
+            .into_iter()
+            .filter(|m| *m != menu_options.mensa),
+    );
+
+    let dates = favorites::upcoming_week(start);
+    let mut published = Vec::new();
+    let mut days = Vec::new();
+    let mut first_error = None;
+    for date in &dates {
+        let mut any_menu = false;
+        let mut candidates = Vec::new();
+        for mensa in &mensas {
+            let options = MenuOptions {
+                date: Some(*date),
+                mensa: mensa.clone(),
+                // each day of the week is planned on its own
+                next_open: false,
+                ..menu_options.clone()
+            };
+            let menu = match menu::get(options, language) {
+                Ok(menu) => menu,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            any_menu = any_menu || menu.closed.is_none();
+            for group in menu.groups.0 {
+                for meal in group.items {
+                    if let Some(price) = meal.price {
+                        candidates.push(Candidate {
+                            mensa: mensa.clone(),
+                            main: fuzzy::fold(&meal.components.main),
+                            price: price.student,
+                            meal,
+                        });
+                    }
+                }
+            }
+        }
+        candidates.sort_by_key(|candidate| candidate.price);
+        candidates.truncate(CANDIDATES_PER_DAY);
+        published.push(any_menu);
+        days.push(candidates);
+    }
+
+    // if not a single menu could be fetched, the cause is more likely the network than closures
+    if let (false, Some(e)) = (published.contains(&true), first_error) {
+        return Err(e);
+    }
+
+    let (total, chosen) = choose(&days, budget, max_red, allow_repeats);
+
+    let remaining = budget.saturating_sub(total);
+    let planned_days = dates
+        .into_iter()
+        .zip(days)
+        .zip(published)
+        .zip(chosen)
+        .map(|(((date, mut candidates), published), index)| match index {
+            Some(index) => {
+                let candidate = candidates.swap_remove(index);
+                Day {
+                    date,
+                    mensa: Some(candidate.mensa),
+                    meal: Some(candidate.meal),
+                    empty_because: None,
+                }
+            }
+            None => Day {
+                date,
+                mensa: None,
+                meal: None,
+                empty_because: Some(if !published {
+                    Reason::NoMenu
+                } else if candidates.is_empty() {
+                    Reason::NoMatchingMeal
+                } else if candidates.iter().all(|c| c.price > remaining) {
+                    Reason::Budget
+                } else {
+                    Reason::Variety
+                }),
+            },
+        })
+        .collect();
+    Ok(Plan {
+        total,
+        budget,
+        days: planned_days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(main: &str, cents: u64, color: &str) -> Candidate {
+        Candidate {
+            mensa: MensaCode(191),
+            meal: serde_json::from_value(serde_json::json!({
+                "name": main,
+                "color": color,
+                "tags": [],
+                "co2_rating": null,
+                "water_rating": null,
+                "price": {"student": cents, "employee": cents, "guest": cents},
+                "allergens": [],
+            }))
+            .unwrap(),
+            price: Cents::from(cents),
+            main: main.to_string(),
+        }
+    }
+
+    #[test]
+    fn leaves_days_empty_that_exceed_the_budget() {
+        let days = vec![
+            vec![candidate("linsen", 200, "green")],
+            vec![candidate("nudeln", 300, "green")],
+            vec![candidate("reis", 250, "green")],
+        ];
+        let (total, chosen) = choose(&days, Cents::from(500), None, false);
+        // two days fit, and skipping the most expensive one is cheapest
+        assert_eq!(total, Cents::from(450));
+        assert_eq!(chosen, vec![Some(0), None, Some(0)]);
+    }
+
+    #[test]
+    fn does_not_repeat_main_components() {
+        let days = vec![
+            vec![candidate("linsen", 200, "green")],
+            vec![
+                candidate("linsen", 200, "green"),
+                candidate("nudeln", 300, "green"),
+            ],
+        ];
+        let (total, chosen) = choose(&days, Cents::from(1000), None, false);
+        assert_eq!(total, Cents::from(500));
+        assert_eq!(chosen, vec![Some(0), Some(1)]);
+        let (total, chosen) = choose(&days, Cents::from(1000), None, true);
+        assert_eq!(total, Cents::from(400));
+        assert_eq!(chosen, vec![Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn limits_the_red_meals() {
+        let days = vec![
+            vec![
+                candidate("wurst", 150, "red"),
+                candidate("tofu", 300, "green"),
+            ],
+            vec![candidate("schnitzel", 150, "red")],
+            vec![candidate("steak", 150, "red")],
+        ];
+        let (total, chosen) = choose(&days, Cents::from(1000), Some(1), false);
+        assert_eq!(total, Cents::from(450));
+        assert_eq!(chosen.iter().filter(|index| index.is_some()).count(), 2);
+        assert_eq!(chosen[0], Some(1));
+        let (_, chosen) = choose(&days, Cents::from(1000), Some(0), false);
+        assert_eq!(chosen, vec![Some(1), None, None]);
+    }
+
+    #[test]
+    fn prefers_more_days_then_the_cheapest_plan() {
+        // the cheap meal on the first day would leave too little for the second
+        let days = vec![
+            vec![
+                candidate("linsen", 100, "green"),
+                candidate("nudeln", 200, "green"),
+            ],
+            vec![
+                candidate("linsen", 300, "green"),
+                candidate("reis", 450, "green"),
+            ],
+        ];
+        let (total, chosen) = choose(&days, Cents::from(500), None, false);
+        assert_eq!(total, Cents::from(500));
+        assert_eq!(chosen, vec![Some(1), Some(0)]);
+
+        let days = vec![
+            vec![
+                candidate("linsen", 100, "green"),
+                candidate("nudeln", 150, "green"),
+            ],
+            vec![
+                candidate("nudeln", 100, "green"),
+                candidate("reis", 200, "green"),
+            ],
+        ];
+        let (total, chosen) = choose(&days, Cents::from(1000), None, false);
+        assert_eq!(total, Cents::from(200));
+        assert_eq!(chosen, vec![Some(0), Some(0)]);
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(feature = "server")]
+use super::utility;
+use super::{
+    codes::{self, Mensa},
+    dates,
+    error::Error,
+    i18n::{self, Language, Text},
+    menu::{self, Meal},
+};
+use ansi_term::{Color, Style};
+use chrono::NaiveDate;
+#[cfg(feature = "server")]
+use rocket::request::{FromQuery, Query};
+use serde_derive::Serialize;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::thread;
+
+/// The number of menus fetched at the same time.
+const PARALLEL_FETCHES: usize = 8;
+
+#[derive(Serialize)]
+pub struct Hit {
+    pub mensa: Mensa,
+    pub meal: Meal,
+}
+
+impl Display for Hit {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            Color::Green.paint(format!("{:>4}", self.mensa.code.to_string())),
+            Style::new().italic().paint(&self.mensa.name),
+            self.meal
+        )
+    }
+}
+
+#[derive(Debug, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct SearchOptions {
+    #[structopt(name = "QUERY", help = i18n::text(Text::HelpQuery))]
+    pub query: String,
+    #[structopt(short, long, parse(try_from_str = dates::parse), help = i18n::text(Text::HelpDate))]
+    pub date: Option<NaiveDate>,
+    #[structopt(short, long, help = i18n::text(Text::HelpSearchUniversity))]
+    pub university: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl<'a> FromQuery<'a> for SearchOptions {
+    type Error = String;
+
+    fn from_query(query: Query<'a>) -> Result<Self, Self::Error> {
+        let q = utility::query_string("q", &query)
+            .ok_or_else(|| i18n::format(Text::MissingParameter, &[&"q"]))?;
+        let date = utility::query_string("date", &query)
+            .map(|value| dates::parse(&value))
+            .transpose()?;
+        Ok(SearchOptions {
+            query: q,
+            date,
+            university: utility::query_string("university", &query),
+        })
+    }
+}
+
+/// Searches the menus of all dining facilities. Facilities whose menu cannot be fetched are
+/// skipped and reported as warnings; only if every fetch failed, the first error is returned.
+pub fn get(options: SearchOptions) -> Result<(Vec<Hit>, Vec<String>), Error> {
+    let date = options.date.unwrap_or_else(dates::default_date);
+    let query = options.query.to_lowercase();
+
+    let mensas = Mutex::new(codes::get(None, options.university.clone())?.into_iter());
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..PARALLEL_FETCHES {
+            scope.spawn(|| loop {
+                let mensa = match mensas.lock().unwrap().next() {
+                    Some(mensa) => mensa,
+                    None => break,
+                };
+                let menu = menu::fetch(&mensa.code, date, Language::De);
+                results.lock().unwrap().push((mensa, menu));
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|(a, _), (b, _)| a.code.cmp(&b.code));
+
+    let mut hits = Vec::new();
+    let mut warnings = Vec::new();
+    let mut first_error = None;
+    let mut fetched = false;
+    for (mensa, menu) in results {
+        let menu = match menu {
+            Ok(menu) => menu,
+            Err(e) => {
+                warnings.push(i18n::format(
+                    Text::FetchFailed,
+                    &[&mensa.code, &date.format("%Y-%m-%d"), &e.message()],
+                ));
+                first_error.get_or_insert(e);
+                continue;
+            }
+        };
+        fetched = true;
+        for group in menu.groups.0 {
+            for meal in group.items {
+                if meal.name.to_lowercase().contains(&query) {
+                    hits.push(Hit {
+                        mensa: mensa.clone(),
+                        meal,
+                    });
+                }
+            }
+        }
+    }
+    hits.sort_by_key(|hit| {
+        hit.meal
+            .price
+            .as_ref()
+            .map(|price| (false, price.student))
+            .unwrap_or((true, 0.into()))
+    });
+    match first_error {
+        Some(e) if !fetched => Err(e),
+        _ => Ok((hits, warnings)),
+    }
+}
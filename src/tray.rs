@@ -0,0 +1,390 @@
+#[cfg(feature = "server")]
+use super::utility;
+use super::{
+    error::Error,
+    i18n::{self, Language, Text},
+    menu::{self, Cents, Color, Course, Meal, MenuOptions},
+};
+use ansi_term::Style;
+#[cfg(feature = "server")]
+use rocket::request::{FromQuery, Query};
+use serde_derive::Serialize;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
+
+/// The most courses on one tray, as the number of combinations grows exponentially with them.
+pub const MAX_COURSES: usize = 5;
+
+/// Parses a weight, which has to be finite to rank the trays.
+pub fn parse_weight(weight: &str) -> Result<f64, String> {
+    weight
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|weight| weight.is_finite())
+        .ok_or_else(|| i18n::format(Text::WrongWeight, &[&weight]))
+}
+
+/// Checks that a tray does not have more than [`MAX_COURSES`] courses.
+fn check_courses(courses: &[Course]) -> Result<(), String> {
+    if courses.len() > MAX_COURSES {
+        Err(i18n::format(
+            Text::TooManyCourses,
+            &[&courses.len(), &MAX_COURSES],
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// How much each property of a meal adds to the score of a tray; lower scores are better.
+#[derive(Debug, Clone, Copy, PartialEq, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Weights {
+    #[structopt(
+        long,
+        default_value = "1",
+        parse(try_from_str = parse_weight),
+        help = i18n::text(Text::HelpPriceWeight)
+    )]
+    pub price_weight: f64,
+    #[structopt(
+        long,
+        default_value = "1",
+        parse(try_from_str = parse_weight),
+        help = i18n::text(Text::HelpColorWeight)
+    )]
+    pub color_weight: f64,
+    #[structopt(
+        long,
+        default_value = "1",
+        parse(try_from_str = parse_weight),
+        help = i18n::text(Text::HelpCo2Weight)
+    )]
+    pub co2_weight: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            price_weight: 1.0,
+            color_weight: 1.0,
+            co2_weight: 1.0,
+        }
+    }
+}
+
+impl Weights {
+    fn score(&self, meal: &Meal) -> f64 {
+        let euros = meal
+            .price
+            .map_or(0.0, |price| u64::from(price.student) as f64 / 100.0);
+        let color = match meal.color {
+            Color::Green => 0.0,
+            Color::Yellow | Color::Unknown(_) => 1.0,
+            Color::Red => 2.0,
+        };
+        // meals without a rating count as average
+        let co2 = meal.co2_rating.map_or(2.0, |rating| rating as u8 as f64);
+        self.price_weight * euros + self.color_weight * color + self.co2_weight * co2
+    }
+}
+
+#[derive(Debug, structopt::StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct TrayOptions {
+    #[structopt(long, help = i18n::text(Text::HelpTrayBudget))]
+    pub budget: Cents,
+    // named apart from the --course filter of the flattened menu options
+    #[structopt(
+        name = "tray-courses",
+        long = "courses",
+        use_delimiter = true,
+        default_value = "main,side,dessert",
+        parse(try_from_str),
+        help = i18n::text(Text::HelpTrayCourses)
+    )]
+    pub courses: Vec<Course>,
+    #[structopt(long, default_value = "5", help = i18n::text(Text::HelpTrayLimit))]
+    pub limit: usize,
+    #[structopt(flatten)]
+    pub weights: Weights,
+    #[structopt(flatten)]
+    pub menu: MenuOptions,
+}
+
+#[cfg(feature = "server")]
+impl<'a> FromQuery<'a> for TrayOptions {
+    type Error = String;
+
+    fn from_query(query: Query<'a>) -> Result<Self, Self::Error> {
+        let budget = utility::query_string("budget", &query)
+            .ok_or_else(|| i18n::format(Text::MissingParameter, &[&"budget"]))?
+            .parse()?;
+        let courses = match utility::query_string("courses", &query) {
+            Some(courses) => courses
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![Course::Main, Course::Side, Course::Dessert],
+        };
+        check_courses(&courses)?;
+        let weight = |key, default| {
+            utility::query_string(key, &query).map_or(Ok(default), |weight| parse_weight(&weight))
+        };
+        let defaults = Weights::default();
+        Ok(TrayOptions {
+            budget,
+            courses,
+            limit: utility::query_value("limit", &query, str::parse).unwrap_or(5),
+            weights: Weights {
+                price_weight: weight("price_weight", defaults.price_weight)?,
+                color_weight: weight("color_weight", defaults.color_weight)?,
+                co2_weight: weight("co2_weight", defaults.co2_weight)?,
+            },
+            menu: MenuOptions::from_query(query)?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct TrayItem {
+    pub course: Course,
+    pub meal: Meal,
+}
+
+#[derive(Serialize)]
+pub struct Tray {
+    pub price: Cents,
+    pub score: f64,
+    pub items: Vec<TrayItem>,
+}
+
+impl Display for Tray {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} {}",
+            Style::new().bold().paint(format!("[{}]", self.price)),
+            Style::new().dimmed().paint(i18n::format(
+                Text::TrayScore,
+                &[&i18n::decimal(self.score, 2)]
+            )),
+        )?;
+        for item in &self.items {
+            write!(
+                f,
+                "  {} {}",
+                Style::new()
+                    .italic()
+                    .paint(format!("{:<12}", item.course.to_string())),
+                item.meal
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+/// A combination of one candidate per course, ordered from the best to the worst score and then
+/// by price.
+struct Combination {
+    score: f64,
+    price: Cents,
+    chosen: Vec<usize>,
+}
+
+impl Ord for Combination {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then(self.price.cmp(&other.price))
+    }
+}
+
+impl PartialOrd for Combination {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Combination {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Combination {}
+
+/// Finds the best `limit` combinations of one candidate per course that stay within the budget,
+/// keeping only those in a heap with the worst on top. Repeated courses pick different meals, and
+/// in only one order.
+fn combine(
+    candidates: &[Vec<(&Meal, f64)>],
+    courses: &[Course],
+    budget: Cents,
+    limit: usize,
+) -> Vec<Combination> {
+    fn step(
+        candidates: &[Vec<(&Meal, f64)>],
+        courses: &[Course],
+        budget: Cents,
+        limit: usize,
+        current: &mut Combination,
+        best: &mut BinaryHeap<Combination>,
+    ) {
+        let position = current.chosen.len();
+        if position == candidates.len() {
+            if best.len() < limit {
+                best.push(Combination {
+                    chosen: current.chosen.clone(),
+                    ..*current
+                });
+            } else if best.peek().is_some_and(|worst| *current < *worst) {
+                best.pop();
+                best.push(Combination {
+                    chosen: current.chosen.clone(),
+                    ..*current
+                });
+            }
+            return;
+        }
+        // after the meal picked for the same course at any earlier position
+        let start = courses[..position]
+            .iter()
+            .rposition(|course| *course == courses[position])
+            .map_or(0, |earlier| current.chosen[earlier] + 1);
+        for (index, (meal, score)) in candidates[position].iter().enumerate().skip(start) {
+            let price = current.price + meal.price.map_or(Cents::default(), |price| price.student);
+            if price <= budget {
+                let (previous_score, previous_price) = (current.score, current.price);
+                current.score += score;
+                current.price = price;
+                current.chosen.push(index);
+                step(candidates, courses, budget, limit, current, best);
+                current.chosen.pop();
+                current.score = previous_score;
+                current.price = previous_price;
+            }
+        }
+    }
+
+    let mut best = BinaryHeap::new();
+    if limit > 0 {
+        let mut current = Combination {
+            score: 0.0,
+            price: Cents::default(),
+            chosen: Vec::with_capacity(candidates.len()),
+        };
+        step(candidates, courses, budget, limit, &mut current, &mut best);
+    }
+    best.into_sorted_vec()
+}
+
+/// Puts together the best trays of one meal per requested course from the filtered menu, within
+/// the budget and ranked by score.
+pub fn get(options: TrayOptions, language: Language) -> Result<Vec<Tray>, Error> {
+    let TrayOptions {
+        budget,
+        courses,
+        limit,
+        weights,
+        menu: menu_options,
+    } = options;
+    check_courses(&courses).map_err(Error::Input)?;
+    let response = menu::get(menu_options, language)?.groups;
+    let candidates = courses
+        .iter()
+        .map(|course| {
+            response
+                .0
+                .iter()
+                .filter(|group| group.course == Some(*course))
+                .flat_map(|group| &group.items)
+                .filter(|meal| meal.price.is_some())
+                .map(|meal| (meal, weights.score(meal)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let trays = combine(&candidates, &courses, budget, limit)
+        .into_iter()
+        .map(|combination| Tray {
+            price: combination.price,
+            score: combination.score,
+            items: courses
+                .iter()
+                .zip(&combination.chosen)
+                .enumerate()
+                .map(|(position, (course, &index))| TrayItem {
+                    course: *course,
+                    meal: candidates[position][index].0.clone(),
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(trays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meal(name: &str, cents: u64) -> Meal {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "color": "green",
+            "tags": [],
+            "co2_rating": null,
+            "water_rating": null,
+            "price": {"student": cents, "employee": cents, "guest": cents},
+            "allergens": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_only_the_best_trays() {
+        let meals = (1..=20)
+            .map(|cents| meal(&cents.to_string(), cents))
+            .collect::<Vec<_>>();
+        let candidates = meals
+            .iter()
+            .map(|meal| (meal, u64::from(meal.price.unwrap().student) as f64))
+            .collect::<Vec<_>>();
+        let courses = [Course::Main, Course::Side];
+        let best = combine(
+            &[candidates.clone(), candidates],
+            &courses,
+            "1".parse().unwrap(),
+            3,
+        );
+        let scores = best.iter().map(|c| c.score).collect::<Vec<_>>();
+        assert_eq!(scores, vec![2.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn picks_different_meals_for_repeated_courses() {
+        let meals = [meal("a", 100), meal("b", 100), meal("c", 100)];
+        let candidates = meals.iter().map(|meal| (meal, 1.0)).collect::<Vec<_>>();
+        let courses = [Course::Main, Course::Side, Course::Main];
+        let best = combine(
+            &[candidates.clone(), candidates.clone(), candidates],
+            &courses,
+            "10".parse().unwrap(),
+            usize::MAX,
+        );
+        assert!(best.iter().all(|c| c.chosen[0] < c.chosen[2]));
+        // three pairs of different mains, each with any of three sides
+        assert_eq!(best.len(), 9);
+    }
+
+    #[test]
+    fn rejects_non_finite_weights_and_too_many_courses() {
+        assert_eq!(parse_weight("0.5"), Ok(0.5));
+        assert!(parse_weight("NaN").is_err());
+        assert!(parse_weight("inf").is_err());
+        assert!(check_courses(&[Course::Main; MAX_COURSES]).is_ok());
+        assert!(check_courses(&[Course::Main; MAX_COURSES + 1]).is_err());
+    }
+}
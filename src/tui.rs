@@ -0,0 +1,573 @@
+use super::{
+    allergens::{self, Allergen},
+    codes::{self, Mensa},
+    dates,
+    error::Error,
+    i18n::{self, Language, Text},
+    menu::{self, Closure, Color, Meal, Menu, MenuOptions, Tag},
+    MensaCode,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use crossterm::{
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::collections::HashMap;
+use std::io;
+use std::panic;
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color as TermColor, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Mensas,
+    Meals,
+    Allergens,
+}
+
+struct App {
+    options: MenuOptions,
+    language: Language,
+    date: NaiveDate,
+    mensas: Vec<Mensa>,
+    mensa_state: ListState,
+    /// The mensa search pattern while it is being typed
+    search: Option<String>,
+    menus: HashMap<(MensaCode, NaiveDate), Result<Menu, String>>,
+    meal_state: ListState,
+    allergens: Vec<Allergen>,
+    allergen_state: ListState,
+    focus: Focus,
+    details: bool,
+}
+
+impl App {
+    fn new(options: MenuOptions, language: Language) -> Result<Self, Error> {
+        let mensas = codes::get(None, None)?;
+        let mut mensa_state = ListState::default();
+        mensa_state.select(mensas.iter().position(|mensa| mensa.code == options.mensa));
+        let mut app = App {
+            date: options.date.unwrap_or_else(dates::default_date),
+            options,
+            language,
+            mensas,
+            mensa_state,
+            search: None,
+            menus: HashMap::new(),
+            meal_state: ListState::default(),
+            allergens: allergens::get()?.items,
+            allergen_state: ListState::default(),
+            focus: Focus::Meals,
+            details: false,
+        };
+        app.allergen_state.select(Some(0));
+        app.load();
+        Ok(app)
+    }
+
+    /// Fetches the menu of the current mensa and date unless it is cached.
+    fn load(&mut self) {
+        let key = (self.options.mensa.clone(), self.date);
+        if !self.menus.contains_key(&key) {
+            let menu = menu::fetch(&key.0, key.1, self.language).map_err(|e| e.to_string());
+            self.menus.insert(key, menu);
+        }
+        self.meal_state.select(Some(0));
+    }
+
+    /// Lists the meals of the current menu that pass the filters, with their group names.
+    fn meals(&self) -> Result<Vec<(&str, &Meal)>, &str> {
+        match self.menus.get(&(self.options.mensa.clone(), self.date)) {
+            Some(Ok(menu)) => Ok(menu
+                .groups
+                .0
+                .iter()
+                .filter(|group| self.options.group_matches(group))
+                .flat_map(|group| group.items.iter().map(move |meal| (&group.name[..], meal)))
+                .filter(|(_, meal)| self.options.meal_matches(meal))
+                .collect()),
+            Some(Err(message)) => Err(message),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn closure(&self) -> Option<&Closure> {
+        match self.menus.get(&(self.options.mensa.clone(), self.date)) {
+            Some(Ok(menu)) => menu.closed.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn selected_meal(&self) -> Option<&Meal> {
+        let meals = self.meals().ok()?;
+        meals
+            .get(self.meal_state.selected()?)
+            .map(|(_, meal)| *meal)
+    }
+
+    fn move_date(&mut self, days: i64) {
+        self.date += Duration::days(days);
+        self.options.date = Some(self.date);
+        self.load();
+    }
+
+    fn choose_mensa(&mut self) {
+        if let Some(mensa) = self
+            .mensa_state
+            .selected()
+            .and_then(|index| self.mensas.get(index))
+        {
+            self.options.mensa = mensa.code.clone();
+            self.load();
+        }
+    }
+
+    fn search_mensas(&mut self, pattern: String) {
+        let pattern = Some(pattern).filter(|p| !p.is_empty());
+        if let Ok(mensas) = codes::get(pattern, None) {
+            self.mensas = mensas;
+            self.mensa_state.select(if self.mensas.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        }
+    }
+
+    fn toggle_color(&mut self, color: Color) {
+        toggle(&mut self.options.colors, color);
+        self.meal_state.select(Some(0));
+    }
+
+    fn toggle_tag(&mut self, tag: Tag) {
+        toggle(&mut self.options.tags, tag);
+        self.meal_state.select(Some(0));
+    }
+
+    fn toggle_allergen(&mut self) {
+        if let Some(allergen) = self
+            .allergen_state
+            .selected()
+            .and_then(|index| self.allergens.get(index))
+            .map(Allergen::code)
+        {
+            toggle(&mut self.options.allergens, allergen);
+            self.meal_state.select(Some(0));
+        }
+    }
+
+    fn allergen_name(&self, code: &str) -> Option<&str> {
+        self.allergens
+            .iter()
+            .find(|allergen| allergen.code() == code)
+            .map(Allergen::name)
+    }
+
+    /// Handles a key press and returns whether the application should keep running.
+    fn handle(&mut self, code: KeyCode) -> bool {
+        if let Some(search) = &mut self.search {
+            match code {
+                KeyCode::Char(c) => search.push(c),
+                KeyCode::Backspace => {
+                    search.pop();
+                }
+                KeyCode::Enter => {
+                    let pattern = self.search.take().unwrap_or_default();
+                    self.search_mensas(pattern);
+                }
+                KeyCode::Esc => self.search = None,
+                _ => {}
+            }
+            return true;
+        }
+        if self.details {
+            self.details = false;
+            return !matches!(code, KeyCode::Char('q'));
+        }
+        match (self.focus, code) {
+            (_, KeyCode::Char('q')) => return false,
+            (_, KeyCode::Esc) if self.focus == Focus::Allergens => self.focus = Focus::Meals,
+            (_, KeyCode::Esc) => return false,
+            (_, KeyCode::Tab) => {
+                self.focus = match self.focus {
+                    Focus::Mensas => Focus::Meals,
+                    _ => Focus::Mensas,
+                }
+            }
+            (_, KeyCode::Left) => self.move_date(-1),
+            (_, KeyCode::Right) => self.move_date(1),
+            (_, KeyCode::Char('/')) => {
+                self.focus = Focus::Mensas;
+                self.search = Some(String::new());
+            }
+            (_, KeyCode::Char('a')) => self.focus = Focus::Allergens,
+            (_, KeyCode::Char('g')) => self.toggle_color(Color::Green),
+            (_, KeyCode::Char('y')) => self.toggle_color(Color::Yellow),
+            (_, KeyCode::Char('r')) => self.toggle_color(Color::Red),
+            (_, KeyCode::Char('v')) => self.toggle_tag(Tag::Vegetarian),
+            (_, KeyCode::Char('e')) => self.toggle_tag(Tag::Vegan),
+            (_, KeyCode::Char('o')) => self.toggle_tag(Tag::Organic),
+            (_, KeyCode::Char('k')) => self.toggle_tag(Tag::ClimateFriendly),
+            (_, KeyCode::Char('f')) => self.toggle_tag(Tag::SustainableFishing),
+            (Focus::Mensas, KeyCode::Up) => step(&mut self.mensa_state, self.mensas.len(), -1),
+            (Focus::Mensas, KeyCode::Down) => step(&mut self.mensa_state, self.mensas.len(), 1),
+            (Focus::Mensas, KeyCode::Enter) => {
+                self.choose_mensa();
+                self.focus = Focus::Meals;
+            }
+            (Focus::Meals, KeyCode::Up) => {
+                let count = self.meals().map_or(0, |meals| meals.len());
+                step(&mut self.meal_state, count, -1)
+            }
+            (Focus::Meals, KeyCode::Down) => {
+                let count = self.meals().map_or(0, |meals| meals.len());
+                step(&mut self.meal_state, count, 1)
+            }
+            (Focus::Meals, KeyCode::Enter) => self.details = self.selected_meal().is_some(),
+            (Focus::Allergens, KeyCode::Up) => {
+                step(&mut self.allergen_state, self.allergens.len(), -1)
+            }
+            (Focus::Allergens, KeyCode::Down) => {
+                step(&mut self.allergen_state, self.allergens.len(), 1)
+            }
+            (Focus::Allergens, KeyCode::Enter) | (Focus::Allergens, KeyCode::Char(' ')) => {
+                self.toggle_allergen()
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+fn toggle<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if let Some(index) = items.iter().position(|x| *x == item) {
+        items.remove(index);
+    } else {
+        items.push(item);
+    }
+}
+
+/// Moves the selection of a list by `delta`, staying within its `length` items.
+fn step(state: &mut ListState, length: usize, delta: isize) {
+    if length == 0 {
+        state.select(None);
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    state.select(Some(
+        (current + delta).clamp(0, length as isize - 1) as usize
+    ));
+}
+
+fn term_color(color: &Color) -> TermColor {
+    match color {
+        Color::Green => TermColor::Green,
+        Color::Yellow => TermColor::Yellow,
+        Color::Red => TermColor::Red,
+        Color::Unknown(_) => TermColor::White,
+    }
+}
+
+fn tag_name(tag: &Tag) -> String {
+    // the tag's Display paints it with ANSI codes, which the terminal backend does not parse
+    i18n::text(match tag {
+        Tag::Vegetarian => Text::Vegetarian,
+        Tag::Vegan => Text::Vegan,
+        Tag::Organic => Text::Organic,
+        Tag::SustainableFishing => Text::SustainableFishing,
+        Tag::ClimateFriendly => Text::ClimateFriendly,
+        Tag::Unknown(_) => Text::UnknownTag,
+    })
+    .to_string()
+}
+
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let margin_x = area.width * (100 - percent_x) / 200;
+    let margin_y = area.height * (100 - percent_y) / 200;
+    Rect::new(
+        area.x + margin_x,
+        area.y + margin_y,
+        area.width - 2 * margin_x,
+        area.height - 2 * margin_y,
+    )
+}
+
+fn block(title: String, focused: bool) -> Block<'static> {
+    let style = if focused {
+        Style::default().fg(TermColor::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+fn draw<B: Backend>(frame: &mut Frame<B>, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[1]);
+
+    let mensa_name = codes::lookup(&app.options.mensa)
+        .map(|mensa| mensa.name)
+        .unwrap_or_else(|| app.options.mensa.to_string());
+    let mut filters = app
+        .options
+        .colors
+        .iter()
+        .map(|color| format!("{:?}", color).to_lowercase())
+        .chain(app.options.tags.iter().map(tag_name))
+        .chain(
+            app.options
+                .allergens
+                .iter()
+                .map(|code| format!("-{}", code)),
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !filters.is_empty() {
+        filters = format!(" [{}]", filters);
+    }
+    frame.render_widget(
+        Paragraph::new(Spans::from(vec![
+            Span::styled(
+                format!(
+                    "{} {} ",
+                    i18n::weekday(app.date.weekday()),
+                    app.date.format("%Y-%m-%d")
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(mensa_name),
+            Span::styled(filters, Style::default().fg(TermColor::Cyan)),
+        ])),
+        rows[0],
+    );
+
+    let mensa_title = match &app.search {
+        Some(search) => format!("{}: {}_", i18n::text(Text::TuiSearch), search),
+        None => i18n::text(Text::TuiMensas).to_string(),
+    };
+    let mensas = List::new(
+        app.mensas
+            .iter()
+            .map(|mensa| ListItem::new(format!("{:>4} {}", mensa.code.to_string(), mensa.name)))
+            .collect::<Vec<_>>(),
+    )
+    .block(block(mensa_title, app.focus == Focus::Mensas))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(mensas, columns[0], &mut app.mensa_state);
+
+    let meal_block = block(
+        i18n::text(Text::TuiMenu).to_string(),
+        app.focus == Focus::Meals,
+    );
+    match app.meals() {
+        Ok(meals) if meals.is_empty() => frame.render_widget(
+            Paragraph::new(match app.closure() {
+                Some(closure) => closure.to_string(),
+                None => i18n::text(Text::TuiNoMeals).to_string(),
+            })
+            .wrap(Wrap { trim: true })
+            .block(meal_block),
+            columns[1],
+        ),
+        Ok(meals) => {
+            let items = meals
+                .iter()
+                .map(|(group, meal)| {
+                    let price = meal
+                        .price
+                        .map(|price| price.student.to_string())
+                        .unwrap_or_default();
+                    let mut spans = vec![
+                        Span::styled(
+                            format!("{:<12.12} ", group),
+                            Style::default().add_modifier(Modifier::DIM),
+                        ),
+                        Span::raw(format!("{:>8} ", price)),
+                        Span::styled(
+                            meal.name.clone(),
+                            Style::default().fg(term_color(&meal.color)),
+                        ),
+                    ];
+                    let favorite = app
+                        .options
+                        .favorites
+                        .iter()
+                        .any(|favorite| favorite.matches(&app.options.mensa, meal));
+                    if favorite {
+                        spans.insert(2, Span::raw("★ "));
+                    }
+                    ListItem::new(Spans::from(spans))
+                })
+                .collect::<Vec<_>>();
+            let list = List::new(items)
+                .block(meal_block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, columns[1], &mut app.meal_state);
+        }
+        Err(message) => frame.render_widget(
+            Paragraph::new(message.to_string())
+                .wrap(Wrap { trim: true })
+                .block(meal_block),
+            columns[1],
+        ),
+    }
+
+    frame.render_widget(
+        Paragraph::new(i18n::text(Text::TuiHelp))
+            .style(Style::default().add_modifier(Modifier::DIM)),
+        rows[2],
+    );
+
+    if app.focus == Focus::Allergens {
+        let area = centered(frame.size(), 60, 70);
+        let items = app
+            .allergens
+            .iter()
+            .map(|allergen| {
+                let code = allergen.code();
+                let mark = if app.options.allergens.contains(&code) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                ListItem::new(format!("{} {:>3} {}", mark, code, allergen.name()))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(block(
+                i18n::text(Text::TuiExcludeAllergens).to_string(),
+                true,
+            ))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut app.allergen_state);
+    }
+
+    if app.details {
+        if let Some(meal) = app.selected_meal() {
+            let area = centered(frame.size(), 70, 70);
+            let mut lines = vec![Spans::from(Span::styled(
+                meal.name.clone(),
+                Style::default()
+                    .fg(term_color(&meal.color))
+                    .add_modifier(Modifier::BOLD),
+            ))];
+            if let Some(price) = &meal.price {
+                lines.push(Spans::from(format!(
+                    "{}: {}   {}: {}   {}: {}",
+                    i18n::text(Text::TuiStudents),
+                    price.student,
+                    i18n::text(Text::TuiEmployees),
+                    price.employee,
+                    i18n::text(Text::TuiGuests),
+                    price.guest
+                )));
+            }
+            let labels = meal
+                .tags
+                .iter()
+                .map(tag_name)
+                .chain(meal.co2_rating.map(|rating| format!("CO2 {}", rating)))
+                .chain(meal.water_rating.map(|rating| format!("H2O {}", rating)))
+                .collect::<Vec<_>>();
+            if !labels.is_empty() {
+                lines.push(Spans::from(labels.join(", ")));
+            }
+            if let Some(nutrition) = &meal.nutrition {
+                lines.push(Spans::from(
+                    nutrition
+                        .kilocalories
+                        .map(|kcal| format!("{} kcal", i18n::decimal(kcal, 0)))
+                        .unwrap_or_default(),
+                ));
+            }
+            lines.push(Spans::from(""));
+            lines.push(Spans::from(Span::styled(
+                i18n::text(Text::TuiAllergens),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            let mut codes = meal.allergens.iter().collect::<Vec<_>>();
+            codes.sort();
+            for code in codes {
+                lines.push(Spans::from(format!(
+                    "{:>3} {}",
+                    code,
+                    app.allergen_name(code).unwrap_or("")
+                )));
+            }
+            let details = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(block(i18n::text(Text::TuiDetails).to_string(), true));
+            frame.render_widget(Clear, area);
+            frame.render_widget(details, area);
+        }
+    }
+}
+
+fn event_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if !app.handle(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Leaves the alternate screen and raw mode again.
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}
+
+/// Restores the terminal when the event loop ends, also by an error or a panic.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+/// Runs the full-screen menu browser, starting with the mensa, date and filters of `options`.
+pub fn run(options: MenuOptions, language: Language) -> Result<(), Error> {
+    let io_error = |e: io::Error| Error::Io(e.to_string());
+    let mut app = App::new(options, language)?;
+
+    // restore the terminal before the panic message is printed, or it would be lost on the
+    // alternate screen
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
+    enable_raw_mode().map_err(io_error)?;
+    let _guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).map_err(io_error)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+        .and_then(|mut terminal| event_loop(&mut terminal, &mut app))
+        .map_err(io_error)
+}
@@ -1,8 +1,13 @@
+#[cfg(feature = "server")]
+use rocket::request::Query;
+#[cfg(feature = "server")]
+use std::str::FromStr;
+
 pub fn partition<A>(predicate: fn(&A) -> bool, xs: &[A]) -> (Vec<&A>, Vec<&A>) {
     let mut toepfchen = Vec::new();
     let mut kroepfchen = Vec::new();
     for x in xs {
-        if predicate(&x) {
+        if predicate(x) {
             toepfchen.push(x);
         } else {
             kroepfchen.push(x);
@@ -10,3 +15,56 @@ pub fn partition<A>(predicate: fn(&A) -> bool, xs: &[A]) -> (Vec<&A>, Vec<&A>) {
     }
     (toepfchen, kroepfchen)
 }
+
+#[cfg(feature = "server")]
+pub fn query_values<T: FromStr>(key: &str, query: &Query) -> Vec<T> {
+    query
+        .clone()
+        .filter_map(|item| {
+            if item.key == key {
+                item.value.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "server")]
+pub fn query_value<T, E>(
+    key: &str,
+    query: &Query,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Option<T> {
+    query
+        .clone()
+        .find(|item| item.key == key)
+        .map(|item| parse(item.value))
+        .and_then(|x| x.ok())
+}
+
+#[cfg(feature = "server")]
+pub fn query_strings(key: &str, query: &Query) -> Vec<String> {
+    query
+        .clone()
+        .filter(|item| item.key == key)
+        .filter_map(|item| item.value.url_decode().ok())
+        .collect()
+}
+
+/// Parses the URL-decoded values of a query parameter, failing on the first invalid one.
+#[cfg(feature = "server")]
+pub fn query_parsed<T: FromStr<Err = String>>(key: &str, query: &Query) -> Result<Vec<T>, String> {
+    query_strings(key, query)
+        .iter()
+        .map(|value| value.parse())
+        .collect()
+}
+
+#[cfg(feature = "server")]
+pub fn query_string(key: &str, query: &Query) -> Option<String> {
+    query
+        .clone()
+        .find(|item| item.key == key)
+        .and_then(|item| item.value.url_decode().ok())
+}